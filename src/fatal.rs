@@ -74,16 +74,24 @@ impl<T, E: fmt::Display> Fatal for Result<T, E> {
     }
 }
 
-/// Print an error message and exit the program.
-pub fn fatal(options: FatalOptions) -> ! {
+/// Print an error message to stderr without exiting, for diagnostics that are
+/// reported as part of a larger batch (see [`fatal`] for the single-error,
+/// exits-immediately case).
+pub fn report(options: FatalOptions) {
     let prefix = format!(
         "{}{}:",
         options.prefix,
         options
             .specifier
+            .as_ref()
             .map(|ps| format!("({})", ps))
             .unwrap_or("".into()),
     );
     eprintln!("{} {}", prefix.bright_red().bold(), options.message);
+}
+
+/// Print an error message and exit the program.
+pub fn fatal(options: FatalOptions) -> ! {
+    report(options);
     process::exit(1);
 }
@@ -6,7 +6,7 @@ use std::path::PathBuf;
 use regex::Regex;
 
 /// A lexical token
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Keyword(Keyword),
     Identifier(String),
@@ -15,7 +15,7 @@ pub enum Token {
 }
 
 /// A keyword (`int`, `return`, `void`, etc)
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Keyword {
     Auto,
     Break,
@@ -55,29 +55,124 @@ pub enum Keyword {
 }
 
 /// A literal value (420, "hello world", etc)
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     Char(char),
-    Integer(i64),
+    Integer(IntegerLiteral),
+    UnsignedInteger(IntegerLiteral),
+    Float(FloatLiteral),
     String(String),
 }
 
-/// A symbol (parentheses, brackets, etc)
-#[derive(Debug, Eq, PartialEq)]
+/// The base an integer literal was written in, so a later type-resolution pass can
+/// tell `0x10` and `16` apart even though they carry the same `value`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum IntegerBase {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+/// An integer literal's value plus the base and suffix (`u`, `l`, `ll`, case-insensitive
+/// combinations of them) it was written with.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct IntegerLiteral {
+    pub value: u64,
+    pub base: IntegerBase,
+    pub suffix: Option<String>,
+}
+
+/// A floating-point literal's value plus the suffix (`f`/`F` for single precision,
+/// otherwise none/`l`/`L`) it was written with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatLiteral {
+    pub value: f64,
+    pub suffix: Option<String>,
+}
+
+/// A symbol (parentheses, brackets, operators, etc)
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Symbol {
     Ampersand,
+    AmpersandAmpersand,
+    Arrow,
     Asterisk,
     BracketOpen,
     BracketClose,
+    Caret,
     Comma,
     Colon,
     Equals,
+    EqualsEquals,
+    Bang,
+    BangEquals,
+    Greater,
+    GreaterEquals,
+    GreaterGreater,
     Hash,
+    Less,
+    LessEquals,
+    LessLess,
+    Minus,
+    MinusEquals,
+    MinusMinus,
     ParenOpen,
     ParenClose,
+    Percent,
+    Period,
+    Pipe,
+    PipePipe,
+    Plus,
+    PlusEquals,
+    PlusPlus,
+    Question,
     Semicolon,
+    Slash,
     SquareBracketOpen,
     SquareBracketClose,
+    Tilde,
+}
+
+/// A position within a source file, used as the start or end of a [`Spanned`] value.
+///
+/// `line` and `column` are 1-indexed. `offset` counts bytes and `column` counts
+/// characters; both point at the next one to be consumed, so a token's `end`
+/// location is exclusive.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Loc {
+    pub offset: usize,
+    pub line: u64,
+    pub column: u64,
+}
+
+impl Default for Loc {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+/// A value with the source span it was produced from attached, mirroring the
+/// `Spanned<Token, Loc, Error>` shape used by production Rust lexers.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub start: Loc,
+    pub end: Loc,
+}
+
+/// The result of lexing a source file: every token that could be recognized,
+/// plus a diagnostic for every span that couldn't. Lexing never aborts early
+/// on an invalid token — it's skipped and scanning continues to EOF, so a
+/// caller sees every error in the file at once rather than just the first.
+#[derive(Debug)]
+pub struct LexedSource {
+    pub tokens: Vec<Spanned<Token>>,
+    pub errors: Vec<LexError>,
 }
 
 /// Type alias for a [`Result`] with an error of type [`LexError`].
@@ -86,12 +181,8 @@ pub type LexResult<T> = Result<T, LexError>;
 /// An error that could be produced during lexical analysis.
 #[derive(thiserror::Error, Debug)]
 pub enum LexError {
-    #[error("invalid token encountered at line {line}, column {column}: {token}")]
-    InvalidToken {
-        token: String,
-        column: u64,
-        line: u64,
-    },
+    #[error("invalid token encountered at line {line}, column {column}: {token}", line = start.line, column = start.column)]
+    InvalidToken { token: String, start: Loc, end: Loc },
 
     #[error("could not read or write a file, see logs for more details")]
     Io(#[from] std::io::Error),
@@ -137,18 +228,43 @@ impl Token {
             "volatile" => Token::Keyword(Keyword::Volatile),
             "while" => Token::Keyword(Keyword::While),
             "&" => Token::Symbol(Symbol::Ampersand),
+            "&&" => Token::Symbol(Symbol::AmpersandAmpersand),
+            "->" => Token::Symbol(Symbol::Arrow),
             "*" => Token::Symbol(Symbol::Asterisk),
             "{" => Token::Symbol(Symbol::BracketOpen),
             "}" => Token::Symbol(Symbol::BracketClose),
+            "^" => Token::Symbol(Symbol::Caret),
             "," => Token::Symbol(Symbol::Comma),
             ":" => Token::Symbol(Symbol::Colon),
             "=" => Token::Symbol(Symbol::Equals),
+            "==" => Token::Symbol(Symbol::EqualsEquals),
+            "!" => Token::Symbol(Symbol::Bang),
+            "!=" => Token::Symbol(Symbol::BangEquals),
+            ">" => Token::Symbol(Symbol::Greater),
+            ">=" => Token::Symbol(Symbol::GreaterEquals),
+            ">>" => Token::Symbol(Symbol::GreaterGreater),
             "#" => Token::Symbol(Symbol::Hash),
+            "<" => Token::Symbol(Symbol::Less),
+            "<=" => Token::Symbol(Symbol::LessEquals),
+            "<<" => Token::Symbol(Symbol::LessLess),
+            "-" => Token::Symbol(Symbol::Minus),
+            "-=" => Token::Symbol(Symbol::MinusEquals),
+            "--" => Token::Symbol(Symbol::MinusMinus),
             "(" => Token::Symbol(Symbol::ParenOpen),
             ")" => Token::Symbol(Symbol::ParenClose),
+            "%" => Token::Symbol(Symbol::Percent),
+            "." => Token::Symbol(Symbol::Period),
+            "|" => Token::Symbol(Symbol::Pipe),
+            "||" => Token::Symbol(Symbol::PipePipe),
+            "+" => Token::Symbol(Symbol::Plus),
+            "+=" => Token::Symbol(Symbol::PlusEquals),
+            "++" => Token::Symbol(Symbol::PlusPlus),
+            "?" => Token::Symbol(Symbol::Question),
             ";" => Token::Symbol(Symbol::Semicolon),
+            "/" => Token::Symbol(Symbol::Slash),
             "[" => Token::Symbol(Symbol::SquareBracketOpen),
             "]" => Token::Symbol(Symbol::SquareBracketClose),
+            "~" => Token::Symbol(Symbol::Tilde),
             _ => {
                 if let Some(literal) = valid_literal(lexer.buffer.as_str()) {
                     return Ok(literal);
@@ -159,8 +275,8 @@ impl Token {
 
                 return Err(LexError::InvalidToken {
                     token: lexer.buffer.clone(),
-                    column: lexer.column - lexer.buffer.len() as u64,
-                    line: lexer.line,
+                    start: lexer.token_start.unwrap_or_else(|| lexer.loc()),
+                    end: lexer.loc(),
                 });
             }
         })
@@ -172,30 +288,231 @@ fn if_valid(string: &str, pattern: &str, f: impl Fn() -> Token) -> Option<Token>
 }
 
 fn valid_literal(string: &str) -> Option<Token> {
-    valid_integer_literal(string)
-        .or(valid_string_literal(string))
-        .or(valid_char_literal(string))
+    valid_numeric_literal(string)
 }
 
-fn valid_integer_literal(string: &str) -> Option<Token> {
-    const PATTERN: &str = "[0-9]+";
-    if_valid(string, PATTERN, || {
-        Token::Literal(Literal::Integer(string.parse().unwrap()))
-    })
+/// Scan `string` as a C numeric literal: a decimal, hexadecimal (`0x`/`0X`), octal
+/// (leading `0`) or binary (`0b`/`0B`) integer, or a (possibly hexadecimal)
+/// floating-point literal, with an optional `u`/`l`/`ll`/`f` suffix (case-insensitive).
+fn valid_numeric_literal(string: &str) -> Option<Token> {
+    let chars: Vec<char> = string.chars().collect();
+    if chars.is_empty() || !(chars[0].is_ascii_digit() || chars[0] == '.') {
+        return None;
+    }
+
+    let (base, digits_start) =
+        if chars.len() > 1 && chars[0] == '0' && matches!(chars[1], 'x' | 'X') {
+            (IntegerBase::Hexadecimal, 2)
+        } else if chars.len() > 1 && chars[0] == '0' && matches!(chars[1], 'b' | 'B') {
+            (IntegerBase::Binary, 2)
+        } else if chars[0] == '0' && chars.len() > 1 && chars[1].is_digit(8) {
+            (IntegerBase::Octal, 1)
+        } else {
+            (IntegerBase::Decimal, 0)
+        };
+
+    let is_base_digit = |c: char| match base {
+        IntegerBase::Hexadecimal => c.is_ascii_hexdigit(),
+        IntegerBase::Binary => c == '0' || c == '1',
+        IntegerBase::Octal => ('0'..='7').contains(&c),
+        IntegerBase::Decimal => c.is_ascii_digit(),
+    };
+
+    let mut i = digits_start;
+    while i < chars.len() && is_base_digit(chars[i]) {
+        i += 1;
+    }
+
+    let allows_float = matches!(base, IntegerBase::Decimal | IntegerBase::Hexadecimal);
+    let mut is_float = false;
+
+    if allows_float && i < chars.len() && chars[i] == '.' {
+        is_float = true;
+        i += 1;
+        while i < chars.len() && is_base_digit(chars[i]) {
+            i += 1;
+        }
+    }
+
+    if allows_float {
+        let exponent_markers: &[char] = match base {
+            IntegerBase::Hexadecimal => &['p', 'P'],
+            _ => &['e', 'E'],
+        };
+        if i < chars.len() && exponent_markers.contains(&chars[i]) {
+            is_float = true;
+            i += 1;
+            if i < chars.len() && matches!(chars[i], '+' | '-') {
+                i += 1;
+            }
+            let exponent_start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            if i == exponent_start {
+                return None;
+            }
+        }
+    }
+
+    if i == digits_start && !is_float {
+        return None;
+    }
+
+    let body: String = chars[..i].iter().collect();
+    let rest: String = chars[i..].iter().collect();
+    let suffix = valid_numeric_suffix(&rest)?;
+
+    if is_float {
+        let value = if base == IntegerBase::Hexadecimal {
+            parse_hex_float(&body)?
+        } else {
+            body.parse().ok()?
+        };
+        return Some(Token::Literal(Literal::Float(FloatLiteral {
+            value,
+            suffix,
+        })));
+    }
+
+    let digits = &body[digits_start..];
+    let digits = if digits.is_empty() { "0" } else { digits };
+    let radix = match base {
+        IntegerBase::Hexadecimal => 16,
+        IntegerBase::Binary => 2,
+        IntegerBase::Octal => 8,
+        IntegerBase::Decimal => 10,
+    };
+    let value = u64::from_str_radix(digits, radix).ok()?;
+    let is_unsigned = suffix
+        .as_deref()
+        .is_some_and(|s| s.to_ascii_lowercase().contains('u'));
+
+    let literal = IntegerLiteral {
+        value,
+        base,
+        suffix,
+    };
+    Some(Token::Literal(if is_unsigned {
+        Literal::UnsignedInteger(literal)
+    } else {
+        Literal::Integer(literal)
+    }))
 }
 
-fn valid_string_literal(string: &str) -> Option<Token> {
-    const PATTERN: &str = "\".*\"";
-    if_valid(string, PATTERN, || {
-        Token::Literal(Literal::String(string.into()))
-    })
+/// Validate (and return) the suffix trailing a numeral, e.g. `u`, `L`, `llu`, `F`.
+/// Returns `None` if `rest` isn't a valid suffix, meaning the literal doesn't match.
+fn valid_numeric_suffix(rest: &str) -> Option<Option<String>> {
+    if rest.is_empty() {
+        return Some(None);
+    }
+    let is_valid = rest.len() <= 3
+        && rest
+            .chars()
+            .all(|c| matches!(c.to_ascii_lowercase(), 'u' | 'l' | 'f'));
+    is_valid.then(|| Some(rest.to_string()))
 }
 
-fn valid_char_literal(string: &str) -> Option<Token> {
-    const PATTERN: &str = "'.*'";
-    if_valid(string, PATTERN, || {
-        Token::Literal(Literal::Char(string.parse().unwrap()))
-    })
+/// Decode a hexadecimal floating-point literal like `0x1.8p3` (mantissa in hex,
+/// binary exponent after `p`/`P`) into its `f64` value.
+fn parse_hex_float(body: &str) -> Option<f64> {
+    let without_prefix = &body[2..];
+    let (mantissa, exponent) = match without_prefix.split_once(['p', 'P']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+        None => (without_prefix, 0),
+    };
+    let (int_part, frac_part) = match mantissa.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, frac_part),
+        None => (mantissa, ""),
+    };
+
+    let mut value = if int_part.is_empty() {
+        0.0
+    } else {
+        u64::from_str_radix(int_part, 16).ok()? as f64
+    };
+
+    let mut scale = 1.0 / 16.0;
+    for c in frac_part.chars() {
+        value += c.to_digit(16)? as f64 * scale;
+        scale /= 16.0;
+    }
+
+    Some(value * 2f64.powi(exponent))
+}
+
+/// Decode the standard C escape sequences (`\n`, `\t`, `\0`, `\xNN`, `\uNNNN`, `\"`,
+/// `\\`, ...) in a raw, delimiter-free char/string literal buffer.
+fn decode_escapes(raw: &str) -> String {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '\\' || i + 1 >= chars.len() {
+            result.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        match chars[i + 1] {
+            'n' => {
+                result.push('\n');
+                i += 2;
+            }
+            't' => {
+                result.push('\t');
+                i += 2;
+            }
+            'r' => {
+                result.push('\r');
+                i += 2;
+            }
+            '0' => {
+                result.push('\0');
+                i += 2;
+            }
+            '\'' => {
+                result.push('\'');
+                i += 2;
+            }
+            '"' => {
+                result.push('"');
+                i += 2;
+            }
+            '\\' => {
+                result.push('\\');
+                i += 2;
+            }
+            'x' => {
+                let start = i + 2;
+                let mut end = start;
+                while end < chars.len() && chars[end].is_ascii_hexdigit() {
+                    end += 1;
+                }
+                let hex: String = chars[start..end].iter().collect();
+                if let Some(decoded) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    result.push(decoded);
+                }
+                i = end;
+            }
+            'u' => {
+                let start = i + 2;
+                let end = (start + 4).min(chars.len());
+                let hex: String = chars[start..end].iter().collect();
+                if let Some(decoded) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    result.push(decoded);
+                }
+                i = end;
+            }
+            other => {
+                result.push(other);
+                i += 2;
+            }
+        }
+    }
+
+    result
 }
 
 fn valid_identifier(string: &str) -> Option<Token> {
@@ -203,18 +520,106 @@ fn valid_identifier(string: &str) -> Option<Token> {
     if_valid(string, PATTERN, || Token::Identifier(string.into()))
 }
 
-/// Tracks state for a lexical analysis run.
-struct Lexer {
-    buffer: String,
-    column: u64,
-    line: u64,
-    tokens: Vec<Token>,
-    context: LexerContext,
+/// The two-character operators recognized by the maximal-munch scanner in
+/// [`Lexer::process_normal`]. Anything starting with one of these characters is held
+/// in [`Lexer::pending_operator`] until the following character confirms or rules out
+/// the longer operator.
+const TWO_CHAR_OPERATORS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "->", "++", "--", "<<", ">>", "+=", "-=",
+];
+
+/// Whether `c` can begin one of [`TWO_CHAR_OPERATORS`], and so needs a one-character
+/// lookahead before it's known to be a single- or double-character symbol.
+fn starts_multi_char_operator(c: char) -> bool {
+    matches!(c, '=' | '!' | '<' | '>' | '&' | '|' | '-' | '+')
+}
+
+/// Whether `first` followed by `second` forms one of [`TWO_CHAR_OPERATORS`].
+fn is_two_char_operator(first: char, second: char) -> bool {
+    let pair: String = [first, second].into_iter().collect();
+    TWO_CHAR_OPERATORS.contains(&pair.as_str())
+}
+
+/// Single-character symbols that never combine with a following character, so they
+/// can be emitted immediately without a lookahead (unlike `/` and the chars in
+/// [`starts_multi_char_operator`]). `.` is handled separately since it can also start
+/// or continue a numeric literal (see [`Lexer::pending_dot`]).
+fn is_immediate_symbol(c: char) -> bool {
+    matches!(
+        c,
+        '(' | ')' | '{' | '}' | ';' | '*' | ',' | ':' | '#' | '[' | ']' | '~' | '^' | '?' | '%'
+    )
 }
 
-enum LexerContext {
+/// Whether `buffer` is the prefix of a numeral (as opposed to e.g. an identifier),
+/// i.e. a `.` or `+`/`-` immediately following it could still be part of the same
+/// [`Literal::Float`] rather than a fresh token.
+fn looks_like_numeric_buffer(buffer: &str) -> bool {
+    matches!(buffer.chars().next(), Some(c) if c.is_ascii_digit() || c == '.')
+}
+
+/// Whether `buffer` ends in the exponent marker for its base (`e`/`E` for decimal,
+/// `p`/`P` for hexadecimal), meaning a following `+`/`-` is the exponent's sign rather
+/// than a fresh operator.
+fn ends_with_exponent_marker(buffer: &str) -> bool {
+    if !looks_like_numeric_buffer(buffer) {
+        return false;
+    }
+    let is_hex = buffer.len() > 1 && buffer.as_bytes()[1].eq_ignore_ascii_case(&b'x');
+    match buffer.chars().last() {
+        Some(last) if is_hex => matches!(last, 'p' | 'P'),
+        Some(last) => matches!(last, 'e' | 'E'),
+        None => false,
+    }
+}
+
+/// A state in the lexer's state stack. Each state defines its own rules for how to
+/// handle a character; when a state's rules don't apply, control passes back to
+/// whichever state is beneath it on the stack once the state is exited (see
+/// [`Lexer::exit_state`]). This is what lets [`LexerState::LineComment`] and friends
+/// bolt on top of [`LexerState::Normal`] without `process` needing to know about them.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum LexerState {
     Normal,
     InString,
+    CharLiteral,
+    LineComment,
+    BlockComment,
+}
+
+/// Tracks state for a lexical analysis run.
+struct Lexer {
+    buffer: String,
+    offset: usize,
+    line: u64,
+    column: u64,
+    /// The [`Loc`] at which the contents currently in `buffer` started accumulating.
+    token_start: Option<Loc>,
+    tokens: Vec<Spanned<Token>>,
+    /// Diagnostics accumulated for tokens that couldn't be recognized, so a
+    /// single invalid token doesn't stop the rest of the file from lexing.
+    errors: Vec<LexError>,
+    /// Stack of active [`LexerState`]s, with the current state on top. Always has at
+    /// least one entry ([`LexerState::Normal`]).
+    states: Vec<LexerState>,
+    /// Start [`Loc`] of a `/` seen in [`LexerState::Normal`] whose role (comment opener
+    /// vs. a token of its own) depends on the character that follows it.
+    pending_slash: Option<Loc>,
+    /// A character seen in [`LexerState::Normal`] that could begin a two-character
+    /// operator (see [`starts_multi_char_operator`]), held until the next character
+    /// confirms whether it forms a [`TWO_CHAR_OPERATORS`] pair or stands alone.
+    pending_operator: Option<(char, Loc)>,
+    /// Start [`Loc`] of a bare `.` seen in [`LexerState::Normal`] whose role (the start
+    /// of a leading-dot [`Literal::Float`] vs. a [`Symbol::Period`] of its own) depends
+    /// on whether the next character is a digit.
+    pending_dot: Option<Loc>,
+    /// Whether the previous character consumed in [`LexerState::BlockComment`] was a `*`,
+    /// i.e. whether the next `/` would close the comment.
+    block_comment_prev_star: bool,
+    /// Whether the character about to be consumed in [`LexerState::InString`] or
+    /// [`LexerState::CharLiteral`] is escaped by a preceding `\`, and so shouldn't be
+    /// treated as the literal's closing quote.
+    escape_next: bool,
 }
 
 impl Lexer {
@@ -222,87 +627,302 @@ impl Lexer {
     fn new() -> Self {
         Self {
             buffer: String::new(),
-            column: 0,
+            offset: 0,
             line: 1,
+            column: 1,
+            token_start: None,
             tokens: Vec::new(),
-            context: LexerContext::Normal,
+            errors: Vec::new(),
+            states: vec![LexerState::Normal],
+            pending_slash: None,
+            pending_operator: None,
+            pending_dot: None,
+            block_comment_prev_star: false,
+            escape_next: false,
         }
     }
 
-    /// Process a character.
-    fn process(&mut self, c: char) -> LexResult<()> {
-        match self.context {
-            LexerContext::Normal => {
-                if c == ' ' || c == '\n' {
-                    self.pop()?;
-                } else if c == '(' || c == ')' || c == '{' || c == '}' || c == ';' || c == '*' {
-                    self.pop()?;
-                    self.push(c);
-                    self.pop()?;
-                } else if c == '"' {
-                    self.context = LexerContext::InString;
-                } else {
-                    self.push(c);
-                }
+    /// The [`Loc`] of the next character to be consumed.
+    fn loc(&self) -> Loc {
+        Loc {
+            offset: self.offset,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    /// The currently active [`LexerState`] (the top of the stack).
+    fn state(&self) -> LexerState {
+        *self
+            .states
+            .last()
+            .expect("state stack should never be empty")
+    }
+
+    /// Push a new state on top of the stack, making it the active state.
+    fn enter_state(&mut self, state: LexerState) {
+        self.states.push(state);
+    }
+
+    /// Pop the active state off the stack, falling back to whichever state is beneath it.
+    fn exit_state(&mut self) {
+        if self.states.len() > 1 {
+            self.states.pop();
+        }
+    }
+
+    /// Process a character according to the rules of the currently active state.
+    fn process(&mut self, c: char) {
+        match self.state() {
+            LexerState::Normal => self.process_normal(c),
+            LexerState::InString => self.process_in_string(c),
+            LexerState::CharLiteral => self.process_char_literal(c),
+            LexerState::LineComment => self.process_line_comment(c),
+            LexerState::BlockComment => self.process_block_comment(c),
+        }
+    }
+
+    fn process_normal(&mut self, c: char) {
+        if let Some((first, start)) = self.pending_operator.take() {
+            self.buffer.push(first);
+            self.token_start = Some(start);
+            if is_two_char_operator(first, c) {
+                self.push(c);
+                self.pop();
+                return;
             }
-            LexerContext::InString => {
-                if c == '"' {
-                    if self.buffer.len() > 0 {
-                        self.tokens
-                            .push(Token::Literal(Literal::String(self.buffer.clone())));
-                        self.buffer.clear();
-                    }
-                    self.context = LexerContext::Normal;
-                } else {
-                    self.push(c);
+            self.pop();
+            return self.process_normal(c);
+        }
+
+        if let Some(start) = self.pending_slash.take() {
+            match c {
+                '/' => {
+                    self.advance(c);
+                    self.enter_state(LexerState::LineComment);
+                    return;
+                }
+                '*' => {
+                    self.advance(c);
+                    self.block_comment_prev_star = false;
+                    self.enter_state(LexerState::BlockComment);
+                    return;
+                }
+                _ => {
+                    self.buffer.push('/');
+                    self.token_start = Some(start);
+                    self.pop();
+                    return self.process_normal(c);
                 }
             }
         }
-        Ok(())
+
+        if let Some(start) = self.pending_dot.take() {
+            self.buffer.push('.');
+            self.token_start = Some(start);
+            if c.is_ascii_digit() {
+                self.push(c);
+                return;
+            }
+            self.pop();
+            return self.process_normal(c);
+        }
+
+        if c == ' ' || c == '\n' {
+            self.pop();
+            self.skip(c);
+        } else if c == '.' && looks_like_numeric_buffer(&self.buffer) {
+            self.push(c);
+        } else if c == '.' && self.buffer.is_empty() {
+            let start = self.loc();
+            self.advance(c);
+            self.pending_dot = Some(start);
+        } else if c == '.' {
+            // Buffer holds something non-numeric (e.g. an identifier): `.` is
+            // unambiguously a member-access `Symbol::Period`.
+            self.pop();
+            self.push(c);
+            self.pop();
+        } else if (c == '+' || c == '-') && ends_with_exponent_marker(&self.buffer) {
+            self.push(c);
+        } else if is_immediate_symbol(c) {
+            self.pop();
+            self.push(c);
+            self.pop();
+        } else if starts_multi_char_operator(c) {
+            self.pop();
+            let start = self.loc();
+            self.advance(c);
+            self.pending_operator = Some((c, start));
+        } else if c == '/' {
+            self.pop();
+            let start = self.loc();
+            self.advance(c);
+            self.pending_slash = Some(start);
+        } else if c == '"' {
+            self.pop();
+            self.skip(c);
+            self.enter_state(LexerState::InString);
+        } else if c == '\'' {
+            self.pop();
+            self.skip(c);
+            self.enter_state(LexerState::CharLiteral);
+        } else {
+            self.push(c);
+        }
+    }
+
+    fn process_in_string(&mut self, c: char) {
+        if self.escape_next {
+            self.escape_next = false;
+            self.push(c);
+        } else if c == '\\' {
+            self.escape_next = true;
+            self.push(c);
+        } else if c == '"' {
+            let decoded = decode_escapes(&self.buffer);
+            self.pop_literal(Literal::String(decoded));
+            self.skip(c);
+            self.exit_state();
+        } else {
+            self.push(c);
+        }
     }
 
-    /// Try to parse a new [`Token`] with the contents of the `buffer` and then clear it.
-    fn pop(&mut self) -> LexResult<()> {
-        if self.buffer.len() > 0 {
-            self.tokens.push(Token::parse(self)?);
+    fn process_char_literal(&mut self, c: char) {
+        if self.escape_next {
+            self.escape_next = false;
+            self.push(c);
+        } else if c == '\\' {
+            self.escape_next = true;
+            self.push(c);
+        } else if c == '\'' {
+            if !self.buffer.is_empty() {
+                let decoded = decode_escapes(&self.buffer);
+                let value = decoded.chars().next().unwrap_or('\0');
+                self.pop_literal(Literal::Char(value));
+            }
+            self.skip(c);
+            self.exit_state();
+        } else {
+            self.push(c);
+        }
+    }
+
+    /// `//` consumes everything up to (and including) the terminating newline.
+    fn process_line_comment(&mut self, c: char) {
+        self.skip(c);
+        if c == '\n' {
+            self.exit_state();
+        }
+    }
+
+    /// `/* ... */` consumes everything up to (and including) the matching `*/`,
+    /// tracking any lines crossed along the way via the usual [`Lexer::advance`].
+    fn process_block_comment(&mut self, c: char) {
+        if self.block_comment_prev_star && c == '/' {
+            self.skip(c);
+            self.exit_state();
+        } else {
+            self.block_comment_prev_star = c == '*';
+            self.skip(c);
+        }
+    }
+
+    /// Try to parse a new [`Token`] with the contents of the `buffer` and then clear it,
+    /// recording its span from `token_start` to the current location. An unrecognized
+    /// buffer is recorded as a diagnostic rather than aborting the rest of the scan.
+    fn pop(&mut self) {
+        if !self.buffer.is_empty() {
+            let start = self.token_start.take().unwrap_or_else(|| self.loc());
+            let end = self.loc();
+            match Token::parse(self) {
+                Ok(value) => self.tokens.push(Spanned { value, start, end }),
+                Err(error) => self.errors.push(error),
+            }
             self.buffer.clear();
         }
-        Ok(())
     }
 
-    /// Add the `character` to the buffer.
+    /// Emit the given `literal`, built by the caller from the raw (delimiter-free) buffer
+    /// contents, skipping the regular [`Token::parse`] dispatch used for bare buffers.
+    fn pop_literal(&mut self, literal: Literal) {
+        let start = self.token_start.take().unwrap_or_else(|| self.loc());
+        let end = self.loc();
+        self.tokens.push(Spanned {
+            value: Token::Literal(literal),
+            start,
+            end,
+        });
+        self.buffer.clear();
+    }
+
+    /// Add the `character` to the buffer, marking it as the token's start location if
+    /// the buffer was empty, then advance past it.
     fn push(&mut self, c: char) {
+        if self.buffer.is_empty() {
+            self.token_start = Some(self.loc());
+        }
         self.buffer.push(c);
-        self.column += 1;
+        self.advance(c);
+    }
+
+    /// Advance past a character that isn't part of any token (whitespace, quotes, etc).
+    fn skip(&mut self, c: char) {
+        self.advance(c);
+    }
+
+    /// Move the current location past `c`.
+    fn advance(&mut self, c: char) {
+        self.offset += c.len_utf8();
         if c == '\n' {
-            self.column = 0;
             self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
         }
     }
 
     /// Try to parse what remains in the buffer so lexical analysis can finish.
-    fn finalize(&mut self) -> LexResult<()> {
+    fn finalize(&mut self) {
+        if let Some((first, start)) = self.pending_operator.take() {
+            self.buffer.push(first);
+            self.token_start = Some(start);
+            self.pop();
+        }
+        if let Some(start) = self.pending_slash.take() {
+            self.buffer.push('/');
+            self.token_start = Some(start);
+            self.pop();
+        }
+        if let Some(start) = self.pending_dot.take() {
+            self.buffer.push('.');
+            self.token_start = Some(start);
+            self.pop();
+        }
         if !self.buffer.is_empty() {
-            self.pop()?;
+            self.pop();
         }
-        Ok(())
     }
 }
 
 /// Perform lexical analysis on the given file.
-pub fn lex(file: PathBuf) -> LexResult<Vec<Token>> {
-    lex_contents(read_to_string(file)?)
+pub fn lex(file: PathBuf) -> LexResult<LexedSource> {
+    Ok(lex_contents(read_to_string(file)?))
 }
 
 /// Perform lexical analysis on the given file contents.
-fn lex_contents(contents: String) -> LexResult<Vec<Token>> {
+fn lex_contents(contents: String) -> LexedSource {
     let mut lexer = Lexer::new();
     for c in contents.chars() {
-        lexer.process(c)?;
+        lexer.process(c);
     }
 
-    lexer.finalize()?;
-    Ok(lexer.tokens)
+    lexer.finalize();
+    LexedSource {
+        tokens: lexer.tokens,
+        errors: lexer.errors,
+    }
 }
 
 #[cfg(test)]
@@ -312,25 +932,35 @@ mod tests {
     fn test_lex_single(string: &str, token: Token) {
         let result = lex_contents(string.to_string());
         assert!(
-            result.is_ok(),
-            "lexical analysis returned non-OK result for '{}' and should have returned a single token {:?} ({:?})",
+            result.errors.is_empty(),
+            "lexical analysis produced errors for '{}' and should have returned a single token {:?} ({:?})",
             string,
             token,
-            result.unwrap_err(),
+            result.errors,
         );
-        assert_eq!(result.unwrap(), vec![token]);
+        let tokens: Vec<Token> = result.tokens.into_iter().map(|s| s.value).collect();
+        assert_eq!(tokens, vec![token]);
     }
 
     fn test_lex(string: &str, tokens: Vec<Token>) {
         let result = lex_contents(string.to_string());
         assert!(
-            result.is_ok(),
-            "lexical analysis returned non-OK result for '{}' and should have returned a list of tokens {:?} ({:?})",
+            result.errors.is_empty(),
+            "lexical analysis produced errors for '{}' and should have returned a list of tokens {:?} ({:?})",
             string,
             tokens,
-            result.unwrap_err(),
+            result.errors,
         );
-        assert_eq!(result.unwrap(), tokens);
+        let actual: Vec<Token> = result.tokens.into_iter().map(|s| s.value).collect();
+        assert_eq!(actual, tokens);
+    }
+
+    fn decimal_integer(value: u64) -> IntegerLiteral {
+        IntegerLiteral {
+            value,
+            base: IntegerBase::Decimal,
+            suffix: None,
+        }
     }
 
     #[test]
@@ -341,7 +971,7 @@ mod tests {
                 Token::Keyword(Keyword::Int),
                 Token::Identifier("foo".into()),
                 Token::Symbol(Symbol::Equals),
-                Token::Literal(Literal::Integer(5)),
+                Token::Literal(Literal::Integer(decimal_integer(5))),
                 Token::Symbol(Symbol::Semicolon),
             ],
         );
@@ -380,10 +1010,291 @@ mod tests {
                 Token::Symbol(Symbol::ParenClose),
                 Token::Symbol(Symbol::BracketOpen),
                 Token::Keyword(Keyword::Return),
-                Token::Literal(Literal::Integer(0)),
+                Token::Literal(Literal::Integer(decimal_integer(0))),
                 Token::Symbol(Symbol::Semicolon),
                 Token::Symbol(Symbol::BracketClose),
             ],
         )
     }
+
+    #[test]
+    fn lexical_analysis_works_on_char_literal() {
+        test_lex(
+            "char c = 'a';",
+            vec![
+                Token::Keyword(Keyword::Char),
+                Token::Identifier("c".into()),
+                Token::Symbol(Symbol::Equals),
+                Token::Literal(Literal::Char('a')),
+                Token::Symbol(Symbol::Semicolon),
+            ],
+        );
+    }
+
+    #[test]
+    fn line_comments_are_dropped_from_the_token_stream() {
+        test_lex(
+            "int foo; // this is a comment\nint bar;",
+            vec![
+                Token::Keyword(Keyword::Int),
+                Token::Identifier("foo".into()),
+                Token::Symbol(Symbol::Semicolon),
+                Token::Keyword(Keyword::Int),
+                Token::Identifier("bar".into()),
+                Token::Symbol(Symbol::Semicolon),
+            ],
+        );
+    }
+
+    #[test]
+    fn block_comments_are_dropped_from_the_token_stream() {
+        test_lex(
+            "int foo; /* this is\na comment */ int bar;",
+            vec![
+                Token::Keyword(Keyword::Int),
+                Token::Identifier("foo".into()),
+                Token::Symbol(Symbol::Semicolon),
+                Token::Keyword(Keyword::Int),
+                Token::Identifier("bar".into()),
+                Token::Symbol(Symbol::Semicolon),
+            ],
+        );
+    }
+
+    #[test]
+    fn spans_point_at_the_correct_line_and_column() {
+        let result = lex_contents("int foo;\n  bar".to_string()).tokens;
+
+        assert_eq!(
+            result[0].start,
+            Loc {
+                offset: 0,
+                line: 1,
+                column: 1
+            }
+        );
+        assert_eq!(
+            result[0].end,
+            Loc {
+                offset: 3,
+                line: 1,
+                column: 4
+            }
+        );
+
+        assert_eq!(
+            result[1].start,
+            Loc {
+                offset: 4,
+                line: 1,
+                column: 5
+            }
+        );
+        assert_eq!(
+            result[1].end,
+            Loc {
+                offset: 7,
+                line: 1,
+                column: 8
+            }
+        );
+
+        // "bar" is on the second line, indented two columns in.
+        assert_eq!(
+            result[3].start,
+            Loc {
+                offset: 11,
+                line: 2,
+                column: 3
+            }
+        );
+        assert_eq!(
+            result[3].end,
+            Loc {
+                offset: 14,
+                line: 2,
+                column: 6
+            }
+        );
+    }
+
+    #[test]
+    fn integer_literals_are_recognized_in_every_base() {
+        test_lex_single(
+            "0x1A",
+            Token::Literal(Literal::Integer(IntegerLiteral {
+                value: 26,
+                base: IntegerBase::Hexadecimal,
+                suffix: None,
+            })),
+        );
+        test_lex_single(
+            "0b101",
+            Token::Literal(Literal::Integer(IntegerLiteral {
+                value: 5,
+                base: IntegerBase::Binary,
+                suffix: None,
+            })),
+        );
+        test_lex_single(
+            "0755",
+            Token::Literal(Literal::Integer(IntegerLiteral {
+                value: 493,
+                base: IntegerBase::Octal,
+                suffix: None,
+            })),
+        );
+    }
+
+    #[test]
+    fn integer_literal_suffixes_are_decoded() {
+        test_lex_single(
+            "10uLL",
+            Token::Literal(Literal::UnsignedInteger(IntegerLiteral {
+                value: 10,
+                base: IntegerBase::Decimal,
+                suffix: Some("uLL".into()),
+            })),
+        );
+    }
+
+    #[test]
+    fn float_literals_are_recognized() {
+        test_lex_single(
+            "1.5",
+            Token::Literal(Literal::Float(FloatLiteral {
+                value: 1.5,
+                suffix: None,
+            })),
+        );
+        test_lex_single(
+            ".5f",
+            Token::Literal(Literal::Float(FloatLiteral {
+                value: 0.5,
+                suffix: Some("f".into()),
+            })),
+        );
+        test_lex_single(
+            "1e10",
+            Token::Literal(Literal::Float(FloatLiteral {
+                value: 1e10,
+                suffix: None,
+            })),
+        );
+        test_lex_single(
+            "0x1.8p3",
+            Token::Literal(Literal::Float(FloatLiteral {
+                value: 12.0,
+                suffix: None,
+            })),
+        );
+    }
+
+    #[test]
+    fn string_and_char_literals_decode_escape_sequences() {
+        test_lex_single(
+            r#""a\nb\t\"\\""#,
+            Token::Literal(Literal::String("a\nb\t\"\\".into())),
+        );
+        test_lex_single("'\\n'", Token::Literal(Literal::Char('\n')));
+        test_lex_single("'\\x41'", Token::Literal(Literal::Char('A')));
+    }
+
+    #[test]
+    fn multi_character_operators_are_maximally_munched() {
+        test_lex(
+            "a->b",
+            vec![
+                Token::Identifier("a".into()),
+                Token::Symbol(Symbol::Arrow),
+                Token::Identifier("b".into()),
+            ],
+        );
+        test_lex(
+            "x<<=2",
+            vec![
+                Token::Identifier("x".into()),
+                Token::Symbol(Symbol::LessLess),
+                Token::Symbol(Symbol::Equals),
+                Token::Literal(Literal::Integer(decimal_integer(2))),
+            ],
+        );
+        test_lex(
+            "x < <y",
+            vec![
+                Token::Identifier("x".into()),
+                Token::Symbol(Symbol::Less),
+                Token::Symbol(Symbol::Less),
+                Token::Identifier("y".into()),
+            ],
+        );
+        test_lex(
+            "i++ + ++j",
+            vec![
+                Token::Identifier("i".into()),
+                Token::Symbol(Symbol::PlusPlus),
+                Token::Symbol(Symbol::Plus),
+                Token::Symbol(Symbol::PlusPlus),
+                Token::Identifier("j".into()),
+            ],
+        );
+    }
+
+    #[test]
+    fn a_lone_slash_is_a_division_symbol_not_a_comment() {
+        test_lex(
+            "a / b",
+            vec![
+                Token::Identifier("a".into()),
+                Token::Symbol(Symbol::Slash),
+                Token::Identifier("b".into()),
+            ],
+        );
+    }
+
+    #[test]
+    fn member_access_period_does_not_get_absorbed_into_a_numeric_literal() {
+        test_lex(
+            "foo.bar",
+            vec![
+                Token::Identifier("foo".into()),
+                Token::Symbol(Symbol::Period),
+                Token::Identifier("bar".into()),
+            ],
+        );
+        test_lex(
+            "1e-5 + x",
+            vec![
+                Token::Literal(Literal::Float(FloatLiteral {
+                    value: 1e-5,
+                    suffix: None,
+                })),
+                Token::Symbol(Symbol::Plus),
+                Token::Identifier("x".into()),
+            ],
+        );
+    }
+
+    #[test]
+    fn an_invalid_token_is_recorded_as_an_error_without_stopping_the_scan() {
+        let result = lex_contents("int $ foo; int @ bar;".to_string());
+        assert_eq!(result.errors.len(), 2);
+        assert!(result
+            .errors
+            .iter()
+            .all(|error| matches!(error, LexError::InvalidToken { .. })));
+
+        let tokens: Vec<Token> = result.tokens.into_iter().map(|s| s.value).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Keyword(Keyword::Int),
+                Token::Identifier("foo".into()),
+                Token::Symbol(Symbol::Semicolon),
+                Token::Keyword(Keyword::Int),
+                Token::Identifier("bar".into()),
+                Token::Symbol(Symbol::Semicolon),
+            ]
+        );
+    }
 }
@@ -1,11 +1,15 @@
 mod fatal;
 mod lexer;
+mod parser;
+mod preprocessor;
 
 use std::path::PathBuf;
+use std::process;
 
 use clap::{Arg, ArgAction, Command};
-use fatal::Fatal;
-use lexer::lex;
+use fatal::{fatal, report, Fatal, FatalOptions};
+use parser::parse;
+use preprocessor::preprocess;
 
 fn main() {
     let matches = Command::new("rcc")
@@ -13,6 +17,12 @@ fn main() {
         .author("Mathew H. <mathewhorner456@gmail.com>")
         .about("A C compiler written in Rust.")
         .arg(Arg::new("FILES").required(true).action(ArgAction::Append))
+        .arg(
+            Arg::new("INCLUDE")
+                .short('I')
+                .action(ArgAction::Append)
+                .help("Adds a directory to the #include search path."),
+        )
         .get_matches();
 
     let files: Vec<String> = matches
@@ -21,19 +31,33 @@ fn main() {
         .map(ToString::to_string)
         .collect();
 
+    let search_paths: Vec<PathBuf> = matches
+        .get_many::<String>("INCLUDE")
+        .map(|paths| paths.map(PathBuf::from).collect())
+        .unwrap_or_default();
+
     let files = validate_files(&files);
 
-    // TEMP: Just lex the first file for now.
+    // TEMP: Just parse the first file for now.
     let file = files.into_iter().next().unwrap();
-    let tokens = lex(file)
-        .map_err(|error| {
-            Fatal::new(error.to_string())
-                .with_prefix_specifier(Some("lexer".to_string()))
-                .exit();
-        })
-        .unwrap();
+    let preprocessed =
+        preprocess(file, &search_paths).fatal(FatalOptions::default().specifier("preprocessor"));
+    for error in &preprocessed.lex_errors {
+        report(FatalOptions::from(error.to_string()).specifier("lexer"));
+    }
+    if !preprocessed.lex_errors.is_empty() {
+        process::exit(1);
+    }
 
-    println!("TOKENS: {:?}", tokens);
+    let parsed = parse(&preprocessed.tokens);
+    for error in &parsed.errors {
+        report(FatalOptions::from(error.to_string()).specifier("parser"));
+    }
+    if !parsed.errors.is_empty() {
+        process::exit(1);
+    }
+
+    println!("{:#?}", parsed.unit);
 }
 
 /// Validate that the given list of source files can be compiled and return them
@@ -46,13 +70,16 @@ fn validate_files(files: &Vec<String>) -> Vec<PathBuf> {
             path.push(file);
 
             if !path.exists() {
-                Fatal::new(format!("No file found with the name \"{}\"!", file)).exit();
+                fatal(FatalOptions::from(format!(
+                    "No file found with the name \"{}\"!",
+                    file
+                )));
             }
             if !file.ends_with(".c") {
-                Fatal::new(format!(
+                fatal(FatalOptions::from(format!(
                     "File with the name \"{}\" does not end with \".c\"!",
                     file
-                ));
+                )));
             }
             path
         })
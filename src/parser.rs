@@ -0,0 +1,1164 @@
+#![allow(dead_code)]
+
+//! The recursive-descent parser: turns the flat, preprocessed token stream
+//! into an AST ready for later semantic analysis and code generation.
+
+use crate::lexer::{Keyword, Literal, Loc, Spanned, Symbol, Token};
+
+/// A full translation unit: the top-level items found in one compiled file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslationUnit {
+    pub items: Vec<ExternalDeclaration>,
+}
+
+/// A top-level item: a function with a body, or a plain declaration
+/// (a global variable, or a function prototype with no body).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExternalDeclaration {
+    Function(FunctionDefinition),
+    Declaration(Declaration),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDefinition {
+    pub return_type: TypeSpecifier,
+    pub name: String,
+    pub params: Vec<Param>,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub type_spec: TypeSpecifier,
+    pub name: Option<String>,
+}
+
+/// A declaration, e.g. `static const int x = 1, *y;` or a function prototype
+/// like `int foo(int x);`, which is simply a declarator whose `params` is
+/// `Some` and whose `initializer` is absent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub type_spec: TypeSpecifier,
+    pub declarators: Vec<InitDeclarator>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InitDeclarator {
+    pub name: String,
+    pub pointer_depth: u32,
+    pub params: Option<Vec<Param>>,
+    pub initializer: Option<Expr>,
+}
+
+/// Storage class, qualifiers and base type built up from the [`Keyword`]s that
+/// can precede a declarator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeSpecifier {
+    pub storage_class: Option<StorageClass>,
+    pub qualifiers: Vec<TypeQualifier>,
+    pub base: BaseType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageClass {
+    Typedef,
+    Extern,
+    Static,
+    Auto,
+    Register,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeQualifier {
+    Const,
+    Volatile,
+    Restrict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseType {
+    Void,
+    Char,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    Signed,
+    Unsigned,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Return(Option<Expr>),
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+    },
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+    },
+    For {
+        init: Option<ForInit>,
+        condition: Option<Expr>,
+        step: Option<Expr>,
+        body: Box<Stmt>,
+    },
+    Compound(Block),
+    Declaration(Declaration),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForInit {
+    Declaration(Declaration),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Literal),
+    Identifier(String),
+    Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Assign {
+        target: Box<Expr>,
+        value: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Negate,
+    Not,
+    BitNot,
+    Deref,
+    AddressOf,
+    PreIncrement,
+    PreDecrement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+/// The result of parsing a token stream: the AST built from every external
+/// declaration that parsed successfully, plus a diagnostic for every one that
+/// didn't. Like [`crate::lexer::lex`], parsing never aborts on the first
+/// error — it recovers by synchronizing to the next `;` or `}` so a caller
+/// sees every error in the file at once rather than just the first.
+#[derive(Debug)]
+pub struct ParsedSource {
+    pub unit: TranslationUnit,
+    pub errors: Vec<ParseError>,
+}
+
+/// Type alias for a [`Result`] with an error of type [`ParseError`].
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// An error that could be produced while parsing a token stream.
+#[derive(thiserror::Error, Debug)]
+pub enum ParseError {
+    #[error("expected {expected}, found {found} at line {line}, column {column}", line = start.line, column = start.column)]
+    UnexpectedToken {
+        expected: String,
+        found: String,
+        start: Loc,
+        end: Loc,
+    },
+}
+
+/// Parse `tokens` (as produced by [`crate::preprocessor::preprocess`]) into a
+/// [`TranslationUnit`], recovering from errors so the whole file is parsed in
+/// a single pass.
+pub fn parse(tokens: &[Spanned<Token>]) -> ParsedSource {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        errors: Vec::new(),
+    };
+    let mut items = Vec::new();
+
+    while parser.pos < parser.tokens.len() {
+        let pos_before_recovery = parser.pos;
+        match parser.parse_external_declaration() {
+            Ok(item) => items.push(item),
+            Err(error) => {
+                parser.errors.push(error);
+                parser.synchronize();
+                if parser.pos == pos_before_recovery {
+                    // `synchronize` deliberately leaves a `}` unconsumed for an
+                    // enclosing block to pick up, but there is no enclosing
+                    // block at the top level, so a stray `}` here would never
+                    // be consumed and the loop would spin forever. Force past
+                    // it so parsing always makes progress.
+                    parser.pos += 1;
+                }
+            }
+        }
+    }
+
+    ParsedSource {
+        unit: TranslationUnit { items },
+        errors: parser.errors,
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Spanned<Token>],
+    pos: usize,
+    /// Diagnostics accumulated for declarations/statements that failed to
+    /// parse, so one bad construct doesn't stop the rest of the file from
+    /// being parsed (see [`Parser::synchronize`]).
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek_token(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.value)
+    }
+
+    fn peek(&self) -> Option<&Spanned<Token>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<&Spanned<Token>> {
+        let token = self.tokens.get(self.pos);
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn check(&self, symbol: Symbol) -> bool {
+        matches!(self.peek_token(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn consume(&mut self, symbol: Symbol) -> bool {
+        if self.check(symbol) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: Symbol, expected: &str) -> ParseResult<()> {
+        if self.consume(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    fn expect_identifier(&mut self) -> ParseResult<String> {
+        match self.peek_token() {
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("an identifier")),
+        }
+    }
+
+    /// Builds an "expected X, found Y" error pointing at the current token
+    /// (or at the end of the file, if there isn't one).
+    fn error(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: describe_token(&token.value),
+                start: token.start,
+                end: token.end,
+            },
+            None => {
+                let loc = self.tokens.last().map(|t| t.end).unwrap_or_default();
+                ParseError::UnexpectedToken {
+                    expected: expected.to_string(),
+                    found: "end of input".to_string(),
+                    start: loc,
+                    end: loc,
+                }
+            }
+        }
+    }
+
+    /// After a parse error, skip tokens until the next `;` (consumed) or `}`
+    /// (left for the enclosing block to consume), so the parser can recover
+    /// and report more than one error per run.
+    fn synchronize(&mut self) {
+        while let Some(token) = self.tokens.get(self.pos) {
+            match &token.value {
+                Token::Symbol(Symbol::Semicolon) => {
+                    self.pos += 1;
+                    return;
+                }
+                Token::Symbol(Symbol::BracketClose) => return,
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn parse_external_declaration(&mut self) -> ParseResult<ExternalDeclaration> {
+        let type_spec = self.parse_type_specifier()?;
+        let pointer_depth = self.parse_pointer_depth();
+        let name = self.expect_identifier()?;
+
+        if self.consume(Symbol::ParenOpen) {
+            let params = self.parse_param_list()?;
+            if self.check(Symbol::BracketOpen) {
+                let body = self.parse_block()?;
+                return Ok(ExternalDeclaration::Function(FunctionDefinition {
+                    return_type: type_spec,
+                    name,
+                    params,
+                    body,
+                }));
+            }
+
+            let first = InitDeclarator {
+                name,
+                pointer_depth,
+                params: Some(params),
+                initializer: None,
+            };
+            let declarators = self.parse_remaining_declarators(first)?;
+            self.expect_symbol(Symbol::Semicolon, "';'")?;
+            return Ok(ExternalDeclaration::Declaration(Declaration {
+                type_spec,
+                declarators,
+            }));
+        }
+
+        let initializer = self.parse_optional_initializer()?;
+        let first = InitDeclarator {
+            name,
+            pointer_depth,
+            params: None,
+            initializer,
+        };
+        let declarators = self.parse_remaining_declarators(first)?;
+        self.expect_symbol(Symbol::Semicolon, "';'")?;
+        Ok(ExternalDeclaration::Declaration(Declaration {
+            type_spec,
+            declarators,
+        }))
+    }
+
+    /// Parses a declaration (no function body allowed), consuming the
+    /// trailing `;`. Used for local declarations inside a block or `for` init.
+    fn parse_declaration(&mut self) -> ParseResult<Declaration> {
+        let type_spec = self.parse_type_specifier()?;
+        let first = self.parse_init_declarator()?;
+        let declarators = self.parse_remaining_declarators(first)?;
+        self.expect_symbol(Symbol::Semicolon, "';'")?;
+        Ok(Declaration {
+            type_spec,
+            declarators,
+        })
+    }
+
+    fn parse_remaining_declarators(
+        &mut self,
+        first: InitDeclarator,
+    ) -> ParseResult<Vec<InitDeclarator>> {
+        let mut declarators = vec![first];
+        while self.consume(Symbol::Comma) {
+            declarators.push(self.parse_init_declarator()?);
+        }
+        Ok(declarators)
+    }
+
+    fn parse_init_declarator(&mut self) -> ParseResult<InitDeclarator> {
+        let pointer_depth = self.parse_pointer_depth();
+        let name = self.expect_identifier()?;
+        let params = if self.consume(Symbol::ParenOpen) {
+            Some(self.parse_param_list()?)
+        } else {
+            None
+        };
+        let initializer = self.parse_optional_initializer()?;
+        Ok(InitDeclarator {
+            name,
+            pointer_depth,
+            params,
+            initializer,
+        })
+    }
+
+    fn parse_optional_initializer(&mut self) -> ParseResult<Option<Expr>> {
+        if self.consume(Symbol::Equals) {
+            Ok(Some(self.parse_assignment()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_pointer_depth(&mut self) -> u32 {
+        let mut depth = 0;
+        while self.consume(Symbol::Asterisk) {
+            depth += 1;
+        }
+        depth
+    }
+
+    fn parse_param_list(&mut self) -> ParseResult<Vec<Param>> {
+        let mut params = Vec::new();
+        if self.consume(Symbol::ParenClose) {
+            return Ok(params);
+        }
+        loop {
+            let type_spec = self.parse_type_specifier()?;
+            self.parse_pointer_depth();
+            let name = match self.peek_token() {
+                Some(Token::Identifier(name)) => {
+                    let name = name.clone();
+                    self.pos += 1;
+                    Some(name)
+                }
+                _ => None,
+            };
+            params.push(Param { type_spec, name });
+            if !self.consume(Symbol::Comma) {
+                break;
+            }
+        }
+        self.expect_symbol(Symbol::ParenClose, "')'")?;
+        Ok(params)
+    }
+
+    fn parse_type_specifier(&mut self) -> ParseResult<TypeSpecifier> {
+        let mut storage_class = None;
+        let mut qualifiers = Vec::new();
+        let mut base = None;
+
+        while let Some(Token::Keyword(keyword)) = self.peek_token() {
+            if let Some(class) = storage_class_for(keyword) {
+                storage_class = Some(class);
+            } else if let Some(qualifier) = qualifier_for(keyword) {
+                qualifiers.push(qualifier);
+            } else if base.is_none() && base_type_for(keyword).is_some() {
+                base = base_type_for(keyword);
+            } else {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        let base = base.ok_or_else(|| self.error("a type"))?;
+        Ok(TypeSpecifier {
+            storage_class,
+            qualifiers,
+            base,
+        })
+    }
+
+    fn parse_block(&mut self) -> ParseResult<Block> {
+        self.expect_symbol(Symbol::BracketOpen, "'{'")?;
+        let mut statements = Vec::new();
+        while !self.check(Symbol::BracketClose) && self.peek().is_some() {
+            match self.parse_statement() {
+                Ok(stmt) => statements.push(stmt),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                }
+            }
+        }
+        self.expect_symbol(Symbol::BracketClose, "'}'")?;
+        Ok(Block { statements })
+    }
+
+    fn parse_statement(&mut self) -> ParseResult<Stmt> {
+        match self.peek_token() {
+            Some(Token::Keyword(Keyword::Return)) => self.parse_return_statement(),
+            Some(Token::Keyword(Keyword::If)) => self.parse_if_statement(),
+            Some(Token::Keyword(Keyword::While)) => self.parse_while_statement(),
+            Some(Token::Keyword(Keyword::For)) => self.parse_for_statement(),
+            Some(Token::Symbol(Symbol::BracketOpen)) => Ok(Stmt::Compound(self.parse_block()?)),
+            Some(Token::Keyword(keyword)) if starts_type_specifier(keyword) => {
+                Ok(Stmt::Declaration(self.parse_declaration()?))
+            }
+            _ => {
+                let expr = self.parse_expression()?;
+                self.expect_symbol(Symbol::Semicolon, "';'")?;
+                Ok(Stmt::Expr(expr))
+            }
+        }
+    }
+
+    fn parse_return_statement(&mut self) -> ParseResult<Stmt> {
+        self.pos += 1; // `return`
+        let value = if self.check(Symbol::Semicolon) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.expect_symbol(Symbol::Semicolon, "';'")?;
+        Ok(Stmt::Return(value))
+    }
+
+    fn parse_if_statement(&mut self) -> ParseResult<Stmt> {
+        self.pos += 1; // `if`
+        self.expect_symbol(Symbol::ParenOpen, "'('")?;
+        let condition = self.parse_expression()?;
+        self.expect_symbol(Symbol::ParenClose, "')'")?;
+        let then_branch = Box::new(self.parse_statement()?);
+        let else_branch = if matches!(self.peek_token(), Some(Token::Keyword(Keyword::Else))) {
+            self.pos += 1;
+            Some(Box::new(self.parse_statement()?))
+        } else {
+            None
+        };
+        Ok(Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    fn parse_while_statement(&mut self) -> ParseResult<Stmt> {
+        self.pos += 1; // `while`
+        self.expect_symbol(Symbol::ParenOpen, "'('")?;
+        let condition = self.parse_expression()?;
+        self.expect_symbol(Symbol::ParenClose, "')'")?;
+        let body = Box::new(self.parse_statement()?);
+        Ok(Stmt::While { condition, body })
+    }
+
+    fn parse_for_statement(&mut self) -> ParseResult<Stmt> {
+        self.pos += 1; // `for`
+        self.expect_symbol(Symbol::ParenOpen, "'('")?;
+
+        let init = if self.consume(Symbol::Semicolon) {
+            None
+        } else if matches!(self.peek_token(), Some(Token::Keyword(keyword)) if starts_type_specifier(keyword))
+        {
+            Some(ForInit::Declaration(self.parse_declaration()?))
+        } else {
+            let expr = self.parse_expression()?;
+            self.expect_symbol(Symbol::Semicolon, "';'")?;
+            Some(ForInit::Expr(expr))
+        };
+
+        let condition = if self.check(Symbol::Semicolon) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.expect_symbol(Symbol::Semicolon, "';'")?;
+
+        let step = if self.check(Symbol::ParenClose) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.expect_symbol(Symbol::ParenClose, "')'")?;
+
+        let body = Box::new(self.parse_statement()?);
+        Ok(Stmt::For {
+            init,
+            condition,
+            step,
+            body,
+        })
+    }
+
+    fn parse_expression(&mut self) -> ParseResult<Expr> {
+        self.parse_assignment()
+    }
+
+    fn parse_assignment(&mut self) -> ParseResult<Expr> {
+        let target = self.parse_logical_or()?;
+        if self.consume(Symbol::Equals) {
+            let value = self.parse_assignment()?;
+            return Ok(Expr::Assign {
+                target: Box::new(target),
+                value: Box::new(value),
+            });
+        }
+        Ok(target)
+    }
+
+    fn parse_logical_or(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(Self::parse_logical_and, &[(Symbol::PipePipe, BinaryOp::Or)])
+    }
+
+    fn parse_logical_and(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(
+            Self::parse_bitwise_or,
+            &[(Symbol::AmpersandAmpersand, BinaryOp::And)],
+        )
+    }
+
+    fn parse_bitwise_or(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(Self::parse_bitwise_xor, &[(Symbol::Pipe, BinaryOp::BitOr)])
+    }
+
+    fn parse_bitwise_xor(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(Self::parse_bitwise_and, &[(Symbol::Caret, BinaryOp::BitXor)])
+    }
+
+    fn parse_bitwise_and(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(
+            Self::parse_equality,
+            &[(Symbol::Ampersand, BinaryOp::BitAnd)],
+        )
+    }
+
+    fn parse_equality(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(
+            Self::parse_relational,
+            &[
+                (Symbol::EqualsEquals, BinaryOp::Eq),
+                (Symbol::BangEquals, BinaryOp::NotEq),
+            ],
+        )
+    }
+
+    fn parse_relational(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(
+            Self::parse_shift,
+            &[
+                (Symbol::Less, BinaryOp::Less),
+                (Symbol::LessEquals, BinaryOp::LessEq),
+                (Symbol::Greater, BinaryOp::Greater),
+                (Symbol::GreaterEquals, BinaryOp::GreaterEq),
+            ],
+        )
+    }
+
+    fn parse_shift(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(
+            Self::parse_additive,
+            &[
+                (Symbol::LessLess, BinaryOp::Shl),
+                (Symbol::GreaterGreater, BinaryOp::Shr),
+            ],
+        )
+    }
+
+    fn parse_additive(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(
+            Self::parse_multiplicative,
+            &[(Symbol::Plus, BinaryOp::Add), (Symbol::Minus, BinaryOp::Sub)],
+        )
+    }
+
+    fn parse_multiplicative(&mut self) -> ParseResult<Expr> {
+        self.parse_binary_left_assoc(
+            Self::parse_unary,
+            &[
+                (Symbol::Asterisk, BinaryOp::Mul),
+                (Symbol::Slash, BinaryOp::Div),
+                (Symbol::Percent, BinaryOp::Mod),
+            ],
+        )
+    }
+
+    /// Precedence-climbing helper shared by every left-associative binary
+    /// level: parse one operand with `operand`, then keep folding in
+    /// `operator symbol -> operand` pairs for as long as `operators` matches.
+    fn parse_binary_left_assoc(
+        &mut self,
+        operand: fn(&mut Self) -> ParseResult<Expr>,
+        operators: &[(Symbol, BinaryOp)],
+    ) -> ParseResult<Expr> {
+        let mut left = operand(self)?;
+        loop {
+            let matched = self.peek_token().and_then(|token| match token {
+                Token::Symbol(symbol) => operators
+                    .iter()
+                    .find(|(candidate, _)| candidate == symbol)
+                    .map(|(_, op)| *op),
+                _ => None,
+            });
+            let Some(op) = matched else { break };
+            self.pos += 1;
+            let right = operand(self)?;
+            left = Expr::Binary {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> ParseResult<Expr> {
+        let op = match self.peek_token() {
+            Some(Token::Symbol(Symbol::Bang)) => Some(UnaryOp::Not),
+            Some(Token::Symbol(Symbol::Minus)) => Some(UnaryOp::Negate),
+            Some(Token::Symbol(Symbol::Tilde)) => Some(UnaryOp::BitNot),
+            Some(Token::Symbol(Symbol::Asterisk)) => Some(UnaryOp::Deref),
+            Some(Token::Symbol(Symbol::Ampersand)) => Some(UnaryOp::AddressOf),
+            Some(Token::Symbol(Symbol::PlusPlus)) => Some(UnaryOp::PreIncrement),
+            Some(Token::Symbol(Symbol::MinusMinus)) => Some(UnaryOp::PreDecrement),
+            Some(Token::Symbol(Symbol::Plus)) => {
+                // Unary `+` has no effect on the operand's value.
+                self.pos += 1;
+                return self.parse_unary();
+            }
+            _ => None,
+        };
+
+        match op {
+            Some(op) => {
+                self.pos += 1;
+                let operand = self.parse_unary()?;
+                Ok(Expr::Unary {
+                    op,
+                    operand: Box::new(operand),
+                })
+            }
+            None => self.parse_postfix(),
+        }
+    }
+
+    fn parse_postfix(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            if self.consume(Symbol::ParenOpen) {
+                let args = self.parse_argument_list()?;
+                expr = Expr::Call {
+                    callee: Box::new(expr),
+                    args,
+                };
+            } else {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    fn parse_argument_list(&mut self) -> ParseResult<Vec<Expr>> {
+        let mut args = Vec::new();
+        if self.consume(Symbol::ParenClose) {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.parse_assignment()?);
+            if !self.consume(Symbol::Comma) {
+                break;
+            }
+        }
+        self.expect_symbol(Symbol::ParenClose, "')'")?;
+        Ok(args)
+    }
+
+    fn parse_primary(&mut self) -> ParseResult<Expr> {
+        match self.peek_token() {
+            Some(Token::Literal(literal)) => {
+                let literal = literal.clone();
+                self.pos += 1;
+                Ok(Expr::Literal(literal))
+            }
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(Expr::Identifier(name))
+            }
+            Some(Token::Symbol(Symbol::ParenOpen)) => {
+                self.pos += 1;
+                let expr = self.parse_expression()?;
+                self.expect_symbol(Symbol::ParenClose, "')'")?;
+                Ok(expr)
+            }
+            _ => Err(self.error("an expression")),
+        }
+    }
+}
+
+fn starts_type_specifier(keyword: &Keyword) -> bool {
+    storage_class_for(keyword).is_some()
+        || qualifier_for(keyword).is_some()
+        || base_type_for(keyword).is_some()
+}
+
+fn storage_class_for(keyword: &Keyword) -> Option<StorageClass> {
+    Some(match keyword {
+        Keyword::Typedef => StorageClass::Typedef,
+        Keyword::Extern => StorageClass::Extern,
+        Keyword::Static => StorageClass::Static,
+        Keyword::Auto => StorageClass::Auto,
+        Keyword::Register => StorageClass::Register,
+        _ => return None,
+    })
+}
+
+fn qualifier_for(keyword: &Keyword) -> Option<TypeQualifier> {
+    Some(match keyword {
+        Keyword::Const => TypeQualifier::Const,
+        Keyword::Volatile => TypeQualifier::Volatile,
+        Keyword::Restrict => TypeQualifier::Restrict,
+        _ => return None,
+    })
+}
+
+fn base_type_for(keyword: &Keyword) -> Option<BaseType> {
+    Some(match keyword {
+        Keyword::Void => BaseType::Void,
+        Keyword::Char => BaseType::Char,
+        Keyword::Short => BaseType::Short,
+        Keyword::Int => BaseType::Int,
+        Keyword::Long => BaseType::Long,
+        Keyword::Float => BaseType::Float,
+        Keyword::Double => BaseType::Double,
+        Keyword::Signed => BaseType::Signed,
+        Keyword::Unsigned => BaseType::Unsigned,
+        _ => return None,
+    })
+}
+
+/// Renders a token as it should appear in an "expected X, found Y" message.
+fn describe_token(token: &Token) -> String {
+    match token {
+        Token::Identifier(name) => format!("identifier \"{name}\""),
+        Token::Keyword(keyword) => format!("keyword \"{}\"", format!("{keyword:?}").to_lowercase()),
+        Token::Literal(_) => "a literal".to_string(),
+        Token::Symbol(symbol) => format!("\"{}\"", symbol_text(symbol)),
+    }
+}
+
+fn symbol_text(symbol: &Symbol) -> &'static str {
+    use Symbol::*;
+    match symbol {
+        Ampersand => "&",
+        AmpersandAmpersand => "&&",
+        Arrow => "->",
+        Asterisk => "*",
+        BracketOpen => "{",
+        BracketClose => "}",
+        Caret => "^",
+        Comma => ",",
+        Colon => ":",
+        Equals => "=",
+        EqualsEquals => "==",
+        Bang => "!",
+        BangEquals => "!=",
+        Greater => ">",
+        GreaterEquals => ">=",
+        GreaterGreater => ">>",
+        Hash => "#",
+        Less => "<",
+        LessEquals => "<=",
+        LessLess => "<<",
+        Minus => "-",
+        MinusEquals => "-=",
+        MinusMinus => "--",
+        ParenOpen => "(",
+        ParenClose => ")",
+        Percent => "%",
+        Period => ".",
+        Pipe => "|",
+        PipePipe => "||",
+        Plus => "+",
+        PlusEquals => "+=",
+        PlusPlus => "++",
+        Question => "?",
+        Semicolon => ";",
+        Slash => "/",
+        SquareBracketOpen => "[",
+        SquareBracketClose => "]",
+        Tilde => "~",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::IntegerLiteral;
+
+    fn loc(line: u64) -> Loc {
+        Loc {
+            offset: 0,
+            line,
+            column: 1,
+        }
+    }
+
+    fn spanned(value: Token, line: u64) -> Spanned<Token> {
+        Spanned {
+            value,
+            start: loc(line),
+            end: loc(line),
+        }
+    }
+
+    fn kw(keyword: Keyword) -> Token {
+        Token::Keyword(keyword)
+    }
+
+    fn sym(symbol: Symbol) -> Token {
+        Token::Symbol(symbol)
+    }
+
+    fn ident(name: &str) -> Token {
+        Token::Identifier(name.to_string())
+    }
+
+    fn int(value: u64) -> Token {
+        Token::Literal(Literal::Integer(IntegerLiteral {
+            value,
+            base: crate::lexer::IntegerBase::Decimal,
+            suffix: None,
+        }))
+    }
+
+    fn int_expr(value: u64) -> Expr {
+        match int(value) {
+            Token::Literal(literal) => Expr::Literal(literal),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Builds the token stream for `int main() { <body tokens> }`.
+    fn wrap_in_main(mut body: Vec<Spanned<Token>>) -> Vec<Spanned<Token>> {
+        let mut tokens = vec![
+            spanned(kw(Keyword::Int), 1),
+            spanned(ident("main"), 1),
+            spanned(sym(Symbol::ParenOpen), 1),
+            spanned(sym(Symbol::ParenClose), 1),
+            spanned(sym(Symbol::BracketOpen), 1),
+        ];
+        tokens.append(&mut body);
+        tokens.push(spanned(sym(Symbol::BracketClose), 1));
+        tokens
+    }
+
+    fn parse_ok(tokens: Vec<Spanned<Token>>) -> TranslationUnit {
+        let parsed = parse(&tokens);
+        assert!(
+            parsed.errors.is_empty(),
+            "parsing produced unexpected errors: {:?}",
+            parsed.errors
+        );
+        parsed.unit
+    }
+
+    fn first_function_body(unit: TranslationUnit) -> Block {
+        match unit.items.into_iter().next() {
+            Some(ExternalDeclaration::Function(function)) => function.body,
+            other => panic!("expected a function definition, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_a_function_with_a_return_statement() {
+        let tokens = wrap_in_main(vec![
+            spanned(kw(Keyword::Return), 2),
+            spanned(int(0), 2),
+            spanned(sym(Symbol::Semicolon), 2),
+        ]);
+        let unit = parse_ok(tokens);
+        assert_eq!(unit.items.len(), 1);
+        let body = first_function_body(unit);
+        assert_eq!(body.statements, vec![Stmt::Return(Some(int_expr(0)))]);
+    }
+
+    #[test]
+    fn binary_operators_respect_c_precedence() {
+        // 1 + 2 * 3
+        let tokens = wrap_in_main(vec![
+            spanned(kw(Keyword::Return), 2),
+            spanned(int(1), 2),
+            spanned(sym(Symbol::Plus), 2),
+            spanned(int(2), 2),
+            spanned(sym(Symbol::Asterisk), 2),
+            spanned(int(3), 2),
+            spanned(sym(Symbol::Semicolon), 2),
+        ]);
+        let body = first_function_body(parse_ok(tokens));
+        let expected = Expr::Binary {
+            op: BinaryOp::Add,
+            left: Box::new(int_expr(1)),
+            right: Box::new(Expr::Binary {
+                op: BinaryOp::Mul,
+                left: Box::new(int_expr(2)),
+                right: Box::new(int_expr(3)),
+            }),
+        };
+        assert_eq!(body.statements, vec![Stmt::Return(Some(expected))]);
+    }
+
+    #[test]
+    fn parses_an_if_else_statement() {
+        let tokens = wrap_in_main(vec![
+            spanned(kw(Keyword::If), 2),
+            spanned(sym(Symbol::ParenOpen), 2),
+            spanned(ident("x"), 2),
+            spanned(sym(Symbol::ParenClose), 2),
+            spanned(kw(Keyword::Return), 2),
+            spanned(int(1), 2),
+            spanned(sym(Symbol::Semicolon), 2),
+            spanned(kw(Keyword::Else), 3),
+            spanned(kw(Keyword::Return), 3),
+            spanned(int(0), 3),
+            spanned(sym(Symbol::Semicolon), 3),
+        ]);
+        let body = first_function_body(parse_ok(tokens));
+        assert_eq!(
+            body.statements,
+            vec![Stmt::If {
+                condition: Expr::Identifier("x".to_string()),
+                then_branch: Box::new(Stmt::Return(Some(int_expr(1)))),
+                else_branch: Some(Box::new(Stmt::Return(Some(int_expr(0))))),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_a_while_loop() {
+        let tokens = wrap_in_main(vec![
+            spanned(kw(Keyword::While), 2),
+            spanned(sym(Symbol::ParenOpen), 2),
+            spanned(ident("x"), 2),
+            spanned(sym(Symbol::ParenClose), 2),
+            spanned(sym(Symbol::BracketOpen), 2),
+            spanned(sym(Symbol::BracketClose), 2),
+        ]);
+        let body = first_function_body(parse_ok(tokens));
+        assert_eq!(
+            body.statements,
+            vec![Stmt::While {
+                condition: Expr::Identifier("x".to_string()),
+                body: Box::new(Stmt::Compound(Block { statements: vec![] })),
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_a_for_loop_with_a_declaration_init() {
+        // for (int i = 0; i; i) {}
+        let tokens = wrap_in_main(vec![
+            spanned(kw(Keyword::For), 2),
+            spanned(sym(Symbol::ParenOpen), 2),
+            spanned(kw(Keyword::Int), 2),
+            spanned(ident("i"), 2),
+            spanned(sym(Symbol::Equals), 2),
+            spanned(int(0), 2),
+            spanned(sym(Symbol::Semicolon), 2),
+            spanned(ident("i"), 2),
+            spanned(sym(Symbol::Semicolon), 2),
+            spanned(ident("i"), 2),
+            spanned(sym(Symbol::ParenClose), 2),
+            spanned(sym(Symbol::BracketOpen), 2),
+            spanned(sym(Symbol::BracketClose), 2),
+        ]);
+        let body = first_function_body(parse_ok(tokens));
+        let Stmt::For { init, .. } = &body.statements[0] else {
+            panic!("expected a for statement, got {:?}", body.statements[0]);
+        };
+        assert!(matches!(init, Some(ForInit::Declaration(_))));
+    }
+
+    #[test]
+    fn parses_a_declaration_with_storage_class_and_qualifier() {
+        let tokens = vec![
+            spanned(kw(Keyword::Static), 1),
+            spanned(kw(Keyword::Const), 1),
+            spanned(kw(Keyword::Int), 1),
+            spanned(ident("x"), 1),
+            spanned(sym(Symbol::Equals), 1),
+            spanned(int(1), 1),
+            spanned(sym(Symbol::Semicolon), 1),
+        ];
+        let unit = parse_ok(tokens);
+        match &unit.items[0] {
+            ExternalDeclaration::Declaration(declaration) => {
+                assert_eq!(declaration.type_spec.storage_class, Some(StorageClass::Static));
+                assert_eq!(declaration.type_spec.qualifiers, vec![TypeQualifier::Const]);
+                assert_eq!(declaration.declarators[0].name, "x");
+                assert_eq!(declaration.declarators[0].initializer, Some(int_expr(1)));
+            }
+            other => panic!("expected a declaration, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_a_function_call_expression() {
+        // foo(1, 2);
+        let tokens = wrap_in_main(vec![
+            spanned(ident("foo"), 2),
+            spanned(sym(Symbol::ParenOpen), 2),
+            spanned(int(1), 2),
+            spanned(sym(Symbol::Comma), 2),
+            spanned(int(2), 2),
+            spanned(sym(Symbol::ParenClose), 2),
+            spanned(sym(Symbol::Semicolon), 2),
+        ]);
+        let body = first_function_body(parse_ok(tokens));
+        assert_eq!(
+            body.statements,
+            vec![Stmt::Expr(Expr::Call {
+                callee: Box::new(Expr::Identifier("foo".to_string())),
+                args: vec![int_expr(1), int_expr(2)],
+            })]
+        );
+    }
+
+    #[test]
+    fn a_missing_semicolon_is_a_parse_error() {
+        let tokens = wrap_in_main(vec![
+            spanned(kw(Keyword::Return), 2),
+            spanned(int(0), 2),
+            // no semicolon
+        ]);
+        let parsed = parse(&tokens);
+        assert_eq!(parsed.errors.len(), 1);
+        assert!(matches!(
+            parsed.errors[0],
+            ParseError::UnexpectedToken { .. }
+        ));
+    }
+
+    #[test]
+    fn recovers_after_an_error_to_report_multiple_diagnostics() {
+        // Two back-to-back malformed declarations at the top level.
+        let tokens = vec![
+            spanned(kw(Keyword::Int), 1),
+            spanned(sym(Symbol::Semicolon), 1),
+            spanned(kw(Keyword::Int), 2),
+            spanned(sym(Symbol::Semicolon), 2),
+        ];
+        let parsed = parse(&tokens);
+        assert_eq!(parsed.errors.len(), 2);
+    }
+}
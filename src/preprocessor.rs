@@ -0,0 +1,1227 @@
+#![allow(dead_code)]
+
+//! The C preprocessor: directive handling, macro expansion and conditional
+//! compilation, run over the token stream produced by [`crate::lexer::lex`]
+//! before the parser ever sees it.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::lexer::{Keyword, LexError, Literal, Loc, Spanned, Symbol, Token, lex};
+
+/// Type alias for a [`Result`] with an error of type [`PreprocessError`].
+pub type PreprocessResult<T> = Result<T, PreprocessError>;
+
+/// An error that could be produced while preprocessing a token stream.
+#[derive(thiserror::Error, Debug)]
+pub enum PreprocessError {
+    #[error("unknown preprocessor directive \"{name}\" at line {line}, column {column}", line = start.line, column = start.column)]
+    UnknownDirective { name: String, start: Loc, end: Loc },
+
+    #[error("expected a macro name at line {line}, column {column}", line = start.line, column = start.column)]
+    ExpectedMacroName { start: Loc, end: Loc },
+
+    #[error("malformed #define parameter list at line {line}, column {column}", line = start.line, column = start.column)]
+    MalformedMacroParams { start: Loc, end: Loc },
+
+    #[error("malformed #include directive at line {line}, column {column}", line = start.line, column = start.column)]
+    MalformedInclude { start: Loc, end: Loc },
+
+    #[error("could not find header \"{name}\" on the include search path (line {line}, column {column})", line = start.line, column = start.column)]
+    IncludeNotFound { name: String, start: Loc, end: Loc },
+
+    #[error("error while lexing file \"{path}\": {source}", path = path.display())]
+    Lex {
+        path: PathBuf,
+        #[source]
+        source: LexError,
+    },
+
+    #[error("#{directive} with no matching #if at line {line}, column {column}", line = start.line, column = start.column)]
+    UnmatchedConditional {
+        directive: String,
+        start: Loc,
+        end: Loc,
+    },
+
+    #[error("unterminated #if starting at line {line}, column {column}", line = start.line, column = start.column)]
+    UnterminatedConditional { start: Loc },
+
+    #[error("macro \"{name}\" expected {expected} argument(s) but got {found} at line {line}, column {column}", line = start.line, column = start.column)]
+    ArgumentCountMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+        start: Loc,
+        end: Loc,
+    },
+
+    #[error("unterminated invocation of macro \"{name}\" at line {line}, column {column}", line = start.line, column = start.column)]
+    UnterminatedInvocation { name: String, start: Loc, end: Loc },
+
+    #[error("invalid #if expression at line {line}, column {column}: {message}", line = start.line, column = start.column)]
+    InvalidConstantExpression {
+        message: String,
+        start: Loc,
+        end: Loc,
+    },
+}
+
+/// A macro registered via `#define`, either object-like (`FOO`) or
+/// function-like (`FOO(a, b)`).
+#[derive(Debug, Clone)]
+enum Macro {
+    ObjectLike {
+        replacement: Vec<Spanned<Token>>,
+    },
+    FunctionLike {
+        params: Vec<String>,
+        replacement: Vec<Spanned<Token>>,
+    },
+}
+
+/// One level of `#if`/`#ifdef`/`#ifndef` nesting.
+#[derive(Debug, Clone, Copy)]
+struct ConditionalFrame {
+    /// Whether the branch currently being accumulated into is active.
+    active: bool,
+    /// Whether some branch of this conditional has already been taken, so
+    /// later `#elif`/`#else` branches must stay inactive.
+    branch_taken: bool,
+    start: Loc,
+}
+
+fn currently_active(stack: &[ConditionalFrame]) -> bool {
+    stack.iter().all(|frame| frame.active)
+}
+
+/// The result of preprocessing: a flat token stream ready for the parser,
+/// plus every [`LexError`] collected while lexing `file` and anything it
+/// `#include`s. Like [`crate::lexer::lex`], a lex error doesn't stop the
+/// run — the offending token is skipped and the rest of the file (and any
+/// further includes) are still processed, so a caller sees every lexical
+/// diagnostic across the whole translation unit at once.
+#[derive(Debug)]
+pub struct PreprocessedSource {
+    pub tokens: Vec<Spanned<Token>>,
+    pub lex_errors: Vec<LexError>,
+}
+
+/// Preprocess `file` (and anything it `#include`s, searched relative to the
+/// including file and then `search_paths` for `"..."` names, and in
+/// `search_paths` only for `<...>` names) into a single flat token stream
+/// ready for the parser.
+pub fn preprocess(
+    file: PathBuf,
+    search_paths: &[PathBuf],
+) -> PreprocessResult<PreprocessedSource> {
+    let mut preprocessor = Preprocessor {
+        macros: HashMap::new(),
+        pragma_once: HashSet::new(),
+        search_paths: search_paths.to_vec(),
+        lex_errors: Vec::new(),
+    };
+    let mut output = Vec::new();
+    preprocessor.process_file(&file, &mut output)?;
+    Ok(PreprocessedSource {
+        tokens: output,
+        lex_errors: preprocessor.lex_errors,
+    })
+}
+
+struct Preprocessor {
+    macros: HashMap<String, Macro>,
+    pragma_once: HashSet<PathBuf>,
+    search_paths: Vec<PathBuf>,
+    /// Lexical diagnostics accumulated across `file` and everything it
+    /// `#include`s, so one typo doesn't hide the rest (see
+    /// [`PreprocessedSource`]).
+    lex_errors: Vec<LexError>,
+}
+
+impl Preprocessor {
+    fn process_file(&mut self, file: &Path, out: &mut Vec<Spanned<Token>>) -> PreprocessResult<()> {
+        let canonical = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        if self.pragma_once.contains(&canonical) {
+            return Ok(());
+        }
+        let lexed = lex(file.to_path_buf()).map_err(|source| PreprocessError::Lex {
+            path: file.to_path_buf(),
+            source,
+        })?;
+        self.lex_errors.extend(lexed.errors);
+        self.process_tokens(file, &lexed.tokens, out)
+    }
+
+    fn process_tokens(
+        &mut self,
+        current_file: &Path,
+        tokens: &[Spanned<Token>],
+        out: &mut Vec<Spanned<Token>>,
+    ) -> PreprocessResult<()> {
+        let mut conditional_stack: Vec<ConditionalFrame> = Vec::new();
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let is_directive_start = matches!(tokens[i].value, Token::Symbol(Symbol::Hash))
+                && (i == 0 || tokens[i - 1].end.line < tokens[i].start.line);
+
+            if is_directive_start {
+                let line_end = Self::directive_line_end(tokens, i);
+                self.handle_directive(
+                    current_file,
+                    &tokens[i],
+                    &tokens[i + 1..line_end],
+                    &mut conditional_stack,
+                    out,
+                )?;
+                i = line_end;
+                continue;
+            }
+
+            if currently_active(&conditional_stack) {
+                self.expand_one(tokens, &mut i, &HashSet::new(), out)?;
+            } else {
+                i += 1;
+            }
+        }
+
+        if let Some(frame) = conditional_stack.last() {
+            return Err(PreprocessError::UnterminatedConditional { start: frame.start });
+        }
+        Ok(())
+    }
+
+    /// Finds the end (exclusive) of the logical line starting at `hash_index`.
+    fn directive_line_end(tokens: &[Spanned<Token>], hash_index: usize) -> usize {
+        let line = tokens[hash_index].start.line;
+        let mut j = hash_index + 1;
+        while j < tokens.len() && tokens[j].start.line == line {
+            j += 1;
+        }
+        j
+    }
+
+    fn handle_directive(
+        &mut self,
+        current_file: &Path,
+        hash: &Spanned<Token>,
+        body: &[Spanned<Token>],
+        conditional_stack: &mut Vec<ConditionalFrame>,
+        out: &mut Vec<Spanned<Token>>,
+    ) -> PreprocessResult<()> {
+        if body.is_empty() {
+            // A lone `#` on its own line is the legal "null directive".
+            return Ok(());
+        }
+
+        let name = directive_name(&body[0]).ok_or_else(|| PreprocessError::UnknownDirective {
+            name: token_text(&body[0].value),
+            start: hash.start,
+            end: body[0].end,
+        })?;
+        let rest = &body[1..];
+        let outer_active = currently_active(conditional_stack);
+
+        match name {
+            "if" => {
+                let active = outer_active && self.evaluate_if_condition(rest, hash)?;
+                conditional_stack.push(ConditionalFrame {
+                    active,
+                    branch_taken: active,
+                    start: hash.start,
+                });
+            }
+            "ifdef" | "ifndef" => {
+                let defined = matches!(rest.first().map(|t| &t.value), Some(Token::Identifier(n)) if self.macros.contains_key(n));
+                let condition = if name == "ifdef" { defined } else { !defined };
+                let active = outer_active && condition;
+                conditional_stack.push(ConditionalFrame {
+                    active,
+                    branch_taken: active,
+                    start: hash.start,
+                });
+            }
+            "elif" => {
+                if conditional_stack.is_empty() {
+                    return Err(PreprocessError::UnmatchedConditional {
+                        directive: "elif".to_string(),
+                        start: hash.start,
+                        end: hash.end,
+                    });
+                }
+                let enclosing_active = conditional_stack[..conditional_stack.len() - 1]
+                    .iter()
+                    .all(|frame| frame.active);
+                let should_evaluate =
+                    enclosing_active && !conditional_stack.last().unwrap().branch_taken;
+                let condition = if should_evaluate {
+                    self.evaluate_if_condition(rest, hash)?
+                } else {
+                    false
+                };
+                let frame = conditional_stack.last_mut().unwrap();
+                frame.active = should_evaluate && condition;
+                if frame.active {
+                    frame.branch_taken = true;
+                }
+            }
+            "else" => {
+                if conditional_stack.is_empty() {
+                    return Err(PreprocessError::UnmatchedConditional {
+                        directive: "else".to_string(),
+                        start: hash.start,
+                        end: hash.end,
+                    });
+                }
+                let enclosing_active = conditional_stack[..conditional_stack.len() - 1]
+                    .iter()
+                    .all(|frame| frame.active);
+                let frame = conditional_stack.last_mut().unwrap();
+                let activate = enclosing_active && !frame.branch_taken;
+                frame.active = activate;
+                if activate {
+                    frame.branch_taken = true;
+                }
+            }
+            "endif" => {
+                conditional_stack
+                    .pop()
+                    .ok_or_else(|| PreprocessError::UnmatchedConditional {
+                        directive: "endif".to_string(),
+                        start: hash.start,
+                        end: hash.end,
+                    })?;
+            }
+            "define" => {
+                if outer_active {
+                    let (macro_name, macro_def) = parse_define(rest, hash)?;
+                    self.macros.insert(macro_name, macro_def);
+                }
+            }
+            "undef" => {
+                if outer_active {
+                    match rest.first().map(|t| &t.value) {
+                        Some(Token::Identifier(name)) => {
+                            self.macros.remove(name);
+                        }
+                        _ => {
+                            return Err(PreprocessError::ExpectedMacroName {
+                                start: hash.start,
+                                end: hash.end,
+                            });
+                        }
+                    }
+                }
+            }
+            "include" => {
+                if outer_active {
+                    let include_path = self.resolve_include(current_file, rest, hash)?;
+                    self.process_file(&include_path, out)?;
+                }
+            }
+            "pragma" => {
+                if outer_active
+                    && matches!(rest.first().map(|t| &t.value), Some(Token::Identifier(n)) if n == "once")
+                {
+                    let canonical = current_file
+                        .canonicalize()
+                        .unwrap_or_else(|_| current_file.to_path_buf());
+                    self.pragma_once.insert(canonical);
+                }
+                // Other pragmas are accepted and silently ignored, matching typical compilers.
+            }
+            other => {
+                return Err(PreprocessError::UnknownDirective {
+                    name: other.to_string(),
+                    start: hash.start,
+                    end: body[0].end,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn resolve_include(
+        &self,
+        current_file: &Path,
+        rest: &[Spanned<Token>],
+        hash: &Spanned<Token>,
+    ) -> PreprocessResult<PathBuf> {
+        let (name, quoted) = match rest.first() {
+            Some(Spanned {
+                value: Token::Literal(Literal::String(name)),
+                ..
+            }) => (name.clone(), true),
+            Some(Spanned {
+                value: Token::Symbol(Symbol::Less),
+                ..
+            }) => {
+                let close = rest
+                    .iter()
+                    .position(|token| matches!(token.value, Token::Symbol(Symbol::Greater)))
+                    .ok_or(PreprocessError::MalformedInclude {
+                        start: hash.start,
+                        end: hash.end,
+                    })?;
+                let name = rest[1..close]
+                    .iter()
+                    .map(|token| token_text(&token.value))
+                    .collect();
+                (name, false)
+            }
+            _ => {
+                return Err(PreprocessError::MalformedInclude {
+                    start: hash.start,
+                    end: hash.end,
+                });
+            }
+        };
+
+        if quoted {
+            if let Some(directory) = current_file.parent() {
+                let candidate = directory.join(&name);
+                if candidate.exists() {
+                    return Ok(candidate);
+                }
+            }
+        }
+        for search_path in &self.search_paths {
+            let candidate = search_path.join(&name);
+            if candidate.exists() {
+                return Ok(candidate);
+            }
+        }
+        Err(PreprocessError::IncludeNotFound {
+            name,
+            start: hash.start,
+            end: hash.end,
+        })
+    }
+
+    fn evaluate_if_condition(
+        &self,
+        line: &[Spanned<Token>],
+        hash: &Spanned<Token>,
+    ) -> PreprocessResult<bool> {
+        let with_defined_resolved = self.substitute_defined(line)?;
+        let mut expanded = Vec::new();
+        self.expand_replacement(&with_defined_resolved, &HashSet::new(), &mut expanded)?;
+        let value = ConstantExprParser::parse(&expanded, hash)?;
+        Ok(value != 0)
+    }
+
+    /// Replaces every `defined NAME` / `defined(NAME)` in `line` with an
+    /// integer literal `1` or `0`, since `defined`'s operand must not be
+    /// macro-expanded like the rest of a `#if` line is.
+    fn substitute_defined(&self, line: &[Spanned<Token>]) -> PreprocessResult<Vec<Spanned<Token>>> {
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < line.len() {
+            if matches!(&line[i].value, Token::Identifier(name) if name == "defined") {
+                let start = line[i].start;
+                let parenthesized = matches!(
+                    line.get(i + 1).map(|t| &t.value),
+                    Some(Token::Symbol(Symbol::ParenOpen))
+                );
+                let (name, end, consumed) = if parenthesized {
+                    match (line.get(i + 2), line.get(i + 3)) {
+                        (
+                            Some(Spanned {
+                                value: Token::Identifier(name),
+                                ..
+                            }),
+                            Some(Spanned {
+                                value: Token::Symbol(Symbol::ParenClose),
+                                end,
+                                ..
+                            }),
+                        ) => (name.clone(), *end, 4),
+                        _ => return Err(PreprocessError::ExpectedMacroName { start, end: start }),
+                    }
+                } else {
+                    match line.get(i + 1) {
+                        Some(Spanned {
+                            value: Token::Identifier(name),
+                            end,
+                            ..
+                        }) => (name.clone(), *end, 2),
+                        _ => return Err(PreprocessError::ExpectedMacroName { start, end: start }),
+                    }
+                };
+                let value = u64::from(self.macros.contains_key(&name));
+                out.push(Spanned {
+                    value: Token::Literal(Literal::Integer(crate::lexer::IntegerLiteral {
+                        value,
+                        base: crate::lexer::IntegerBase::Decimal,
+                        suffix: None,
+                    })),
+                    start,
+                    end,
+                });
+                i += consumed;
+            } else {
+                out.push(line[i].clone());
+                i += 1;
+            }
+        }
+        Ok(out)
+    }
+
+    fn expand_replacement(
+        &self,
+        tokens: &[Spanned<Token>],
+        hide_set: &HashSet<String>,
+        out: &mut Vec<Spanned<Token>>,
+    ) -> PreprocessResult<()> {
+        let mut i = 0;
+        while i < tokens.len() {
+            self.expand_one(tokens, &mut i, hide_set, out)?;
+        }
+        Ok(())
+    }
+
+    /// Expands the token at `tokens[*i]`, advancing `*i` past whatever was
+    /// consumed (a single token, or an entire macro invocation), appending
+    /// the result to `out`.
+    fn expand_one(
+        &self,
+        tokens: &[Spanned<Token>],
+        i: &mut usize,
+        hide_set: &HashSet<String>,
+        out: &mut Vec<Spanned<Token>>,
+    ) -> PreprocessResult<()> {
+        let token = tokens[*i].clone();
+        if let Token::Identifier(name) = &token.value {
+            if !hide_set.contains(name) {
+                if let Some(macro_def) = self.macros.get(name).cloned() {
+                    match macro_def {
+                        Macro::ObjectLike { replacement } => {
+                            *i += 1;
+                            let mut next_hide_set = hide_set.clone();
+                            next_hide_set.insert(name.clone());
+                            return self.expand_replacement(&replacement, &next_hide_set, out);
+                        }
+                        Macro::FunctionLike {
+                            params,
+                            replacement,
+                        } => {
+                            let is_invocation = matches!(
+                                tokens.get(*i + 1).map(|t| &t.value),
+                                Some(Token::Symbol(Symbol::ParenOpen))
+                            );
+                            if is_invocation {
+                                let (args, end) = collect_arguments(
+                                    tokens,
+                                    *i + 2,
+                                    name,
+                                    token.start,
+                                    token.end,
+                                )?;
+                                if args.len() != params.len() {
+                                    return Err(PreprocessError::ArgumentCountMismatch {
+                                        name: name.clone(),
+                                        expected: params.len(),
+                                        found: args.len(),
+                                        start: token.start,
+                                        end: token.end,
+                                    });
+                                }
+                                let substituted = substitute_params(&replacement, &params, &args);
+                                *i = end;
+                                let mut next_hide_set = hide_set.clone();
+                                next_hide_set.insert(name.clone());
+                                return self.expand_replacement(
+                                    &substituted,
+                                    &next_hide_set,
+                                    out,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        out.push(token);
+        *i += 1;
+        Ok(())
+    }
+}
+
+fn directive_name(token: &Spanned<Token>) -> Option<&'static str> {
+    match &token.value {
+        Token::Keyword(Keyword::If) => Some("if"),
+        Token::Keyword(Keyword::Else) => Some("else"),
+        Token::Identifier(name) => match name.as_str() {
+            "ifdef" => Some("ifdef"),
+            "ifndef" => Some("ifndef"),
+            "elif" => Some("elif"),
+            "endif" => Some("endif"),
+            "define" => Some("define"),
+            "undef" => Some("undef"),
+            "include" => Some("include"),
+            "pragma" => Some("pragma"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn parse_define(
+    body: &[Spanned<Token>],
+    hash: &Spanned<Token>,
+) -> PreprocessResult<(String, Macro)> {
+    let mut idx = 0;
+    let name = match body.get(idx) {
+        Some(Spanned {
+            value: Token::Identifier(name),
+            ..
+        }) => name.clone(),
+        _ => {
+            return Err(PreprocessError::ExpectedMacroName {
+                start: hash.start,
+                end: hash.end,
+            });
+        }
+    };
+    idx += 1;
+
+    if matches!(
+        body.get(idx).map(|t| &t.value),
+        Some(Token::Symbol(Symbol::ParenOpen))
+    ) {
+        idx += 1;
+        let mut params = Vec::new();
+        loop {
+            match body.get(idx).map(|t| &t.value) {
+                Some(Token::Symbol(Symbol::ParenClose)) => {
+                    idx += 1;
+                    break;
+                }
+                Some(Token::Identifier(param)) => {
+                    params.push(param.clone());
+                    idx += 1;
+                    match body.get(idx).map(|t| &t.value) {
+                        Some(Token::Symbol(Symbol::Comma)) => idx += 1,
+                        Some(Token::Symbol(Symbol::ParenClose)) => {
+                            idx += 1;
+                            break;
+                        }
+                        _ => {
+                            return Err(PreprocessError::MalformedMacroParams {
+                                start: hash.start,
+                                end: hash.end,
+                            });
+                        }
+                    }
+                }
+                _ => {
+                    return Err(PreprocessError::MalformedMacroParams {
+                        start: hash.start,
+                        end: hash.end,
+                    });
+                }
+            }
+        }
+        let replacement = body[idx..].to_vec();
+        Ok((
+            name,
+            Macro::FunctionLike {
+                params,
+                replacement,
+            },
+        ))
+    } else {
+        let replacement = body[idx..].to_vec();
+        Ok((name, Macro::ObjectLike { replacement }))
+    }
+}
+
+/// Collects the comma-separated argument token sequences of a function-like
+/// macro invocation, starting just after its opening `(` at `start_idx`.
+/// Returns the arguments and the index just past the matching `)`.
+fn collect_arguments(
+    tokens: &[Spanned<Token>],
+    start_idx: usize,
+    name: &str,
+    call_start: Loc,
+    call_end: Loc,
+) -> PreprocessResult<(Vec<Vec<Spanned<Token>>>, usize)> {
+    if matches!(
+        tokens.get(start_idx).map(|t| &t.value),
+        Some(Token::Symbol(Symbol::ParenClose))
+    ) {
+        return Ok((Vec::new(), start_idx + 1));
+    }
+
+    let mut args: Vec<Vec<Spanned<Token>>> = Vec::new();
+    let mut current: Vec<Spanned<Token>> = Vec::new();
+    let mut depth = 0u32;
+    let mut idx = start_idx;
+
+    loop {
+        match tokens.get(idx) {
+            None => {
+                return Err(PreprocessError::UnterminatedInvocation {
+                    name: name.to_string(),
+                    start: call_start,
+                    end: call_end,
+                });
+            }
+            Some(Spanned {
+                value: Token::Symbol(Symbol::ParenOpen),
+                ..
+            }) => {
+                depth += 1;
+                current.push(tokens[idx].clone());
+                idx += 1;
+            }
+            Some(Spanned {
+                value: Token::Symbol(Symbol::ParenClose),
+                ..
+            }) if depth == 0 => {
+                args.push(std::mem::take(&mut current));
+                idx += 1;
+                break;
+            }
+            Some(Spanned {
+                value: Token::Symbol(Symbol::ParenClose),
+                ..
+            }) => {
+                depth -= 1;
+                current.push(tokens[idx].clone());
+                idx += 1;
+            }
+            Some(Spanned {
+                value: Token::Symbol(Symbol::Comma),
+                ..
+            }) if depth == 0 => {
+                args.push(std::mem::take(&mut current));
+                idx += 1;
+            }
+            Some(_) => {
+                current.push(tokens[idx].clone());
+                idx += 1;
+            }
+        }
+    }
+
+    Ok((args, idx))
+}
+
+fn substitute_params(
+    replacement: &[Spanned<Token>],
+    params: &[String],
+    args: &[Vec<Spanned<Token>>],
+) -> Vec<Spanned<Token>> {
+    let mut out = Vec::new();
+    for token in replacement {
+        if let Token::Identifier(name) = &token.value {
+            if let Some(position) = params.iter().position(|param| param == name) {
+                out.extend(args[position].iter().cloned());
+                continue;
+            }
+        }
+        out.push(token.clone());
+    }
+    out
+}
+
+/// Renders a token back to roughly the source text it came from, used to
+/// reconstruct angle-bracket `#include <...>` header names.
+fn token_text(token: &Token) -> String {
+    match token {
+        Token::Identifier(name) => name.clone(),
+        Token::Keyword(keyword) => format!("{keyword:?}").to_lowercase(),
+        Token::Literal(Literal::Integer(integer)) => integer.value.to_string(),
+        Token::Literal(Literal::UnsignedInteger(integer)) => integer.value.to_string(),
+        Token::Literal(Literal::Float(float)) => float.value.to_string(),
+        Token::Literal(Literal::Char(c)) => c.to_string(),
+        Token::Literal(Literal::String(s)) => s.clone(),
+        Token::Symbol(symbol) => symbol_text(symbol).to_string(),
+    }
+}
+
+fn symbol_text(symbol: &Symbol) -> &'static str {
+    use Symbol::*;
+    match symbol {
+        Ampersand => "&",
+        AmpersandAmpersand => "&&",
+        Arrow => "->",
+        Asterisk => "*",
+        BracketOpen => "{",
+        BracketClose => "}",
+        Caret => "^",
+        Comma => ",",
+        Colon => ":",
+        Equals => "=",
+        EqualsEquals => "==",
+        Bang => "!",
+        BangEquals => "!=",
+        Greater => ">",
+        GreaterEquals => ">=",
+        GreaterGreater => ">>",
+        Hash => "#",
+        Less => "<",
+        LessEquals => "<=",
+        LessLess => "<<",
+        Minus => "-",
+        MinusEquals => "-=",
+        MinusMinus => "--",
+        ParenOpen => "(",
+        ParenClose => ")",
+        Percent => "%",
+        Period => ".",
+        Pipe => "|",
+        PipePipe => "||",
+        Plus => "+",
+        PlusEquals => "+=",
+        PlusPlus => "++",
+        Question => "?",
+        Semicolon => ";",
+        Slash => "/",
+        SquareBracketOpen => "[",
+        SquareBracketClose => "]",
+        Tilde => "~",
+    }
+}
+
+/// A small recursive-descent evaluator for the constant integer expressions
+/// that follow `#if`/`#elif`, precedence-climbing the same binary operator
+/// levels as the expression parser in [`crate::parser`].
+struct ConstantExprParser<'a> {
+    tokens: &'a [Spanned<Token>],
+    pos: usize,
+    hash: &'a Spanned<Token>,
+}
+
+impl<'a> ConstantExprParser<'a> {
+    fn parse(tokens: &'a [Spanned<Token>], hash: &'a Spanned<Token>) -> PreprocessResult<i64> {
+        let mut parser = Self {
+            tokens,
+            pos: 0,
+            hash,
+        };
+        let value = parser.parse_logical_or()?;
+        if parser.pos != parser.tokens.len() {
+            return Err(parser.error("unexpected trailing tokens"));
+        }
+        Ok(value)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.value)
+    }
+
+    fn error(&self, message: &str) -> PreprocessError {
+        PreprocessError::InvalidConstantExpression {
+            message: message.to_string(),
+            start: self.hash.start,
+            end: self.hash.end,
+        }
+    }
+
+    fn parse_logical_or(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_logical_and()?;
+        while matches!(self.peek(), Some(Token::Symbol(Symbol::PipePipe))) {
+            self.pos += 1;
+            let right = self.parse_logical_and()?;
+            left = i64::from(left != 0 || right != 0);
+        }
+        Ok(left)
+    }
+
+    fn parse_logical_and(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_bitwise_or()?;
+        while matches!(self.peek(), Some(Token::Symbol(Symbol::AmpersandAmpersand))) {
+            self.pos += 1;
+            let right = self.parse_bitwise_or()?;
+            left = i64::from(left != 0 && right != 0);
+        }
+        Ok(left)
+    }
+
+    fn parse_bitwise_or(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_bitwise_xor()?;
+        while matches!(self.peek(), Some(Token::Symbol(Symbol::Pipe))) {
+            self.pos += 1;
+            left |= self.parse_bitwise_xor()?;
+        }
+        Ok(left)
+    }
+
+    fn parse_bitwise_xor(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_bitwise_and()?;
+        while matches!(self.peek(), Some(Token::Symbol(Symbol::Caret))) {
+            self.pos += 1;
+            left ^= self.parse_bitwise_and()?;
+        }
+        Ok(left)
+    }
+
+    fn parse_bitwise_and(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_equality()?;
+        while matches!(self.peek(), Some(Token::Symbol(Symbol::Ampersand))) {
+            self.pos += 1;
+            left &= self.parse_equality()?;
+        }
+        Ok(left)
+    }
+
+    fn parse_equality(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_relational()?;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol(Symbol::EqualsEquals)) => {
+                    self.pos += 1;
+                    let right = self.parse_relational()?;
+                    left = i64::from(left == right);
+                }
+                Some(Token::Symbol(Symbol::BangEquals)) => {
+                    self.pos += 1;
+                    let right = self.parse_relational()?;
+                    left = i64::from(left != right);
+                }
+                _ => break,
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_relational(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_shift()?;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol(Symbol::Less)) => {
+                    self.pos += 1;
+                    let right = self.parse_shift()?;
+                    left = i64::from(left < right);
+                }
+                Some(Token::Symbol(Symbol::LessEquals)) => {
+                    self.pos += 1;
+                    let right = self.parse_shift()?;
+                    left = i64::from(left <= right);
+                }
+                Some(Token::Symbol(Symbol::Greater)) => {
+                    self.pos += 1;
+                    let right = self.parse_shift()?;
+                    left = i64::from(left > right);
+                }
+                Some(Token::Symbol(Symbol::GreaterEquals)) => {
+                    self.pos += 1;
+                    let right = self.parse_shift()?;
+                    left = i64::from(left >= right);
+                }
+                _ => break,
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_shift(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_additive()?;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol(Symbol::LessLess)) => {
+                    self.pos += 1;
+                    left <<= self.parse_additive()?;
+                }
+                Some(Token::Symbol(Symbol::GreaterGreater)) => {
+                    self.pos += 1;
+                    left >>= self.parse_additive()?;
+                }
+                _ => break,
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol(Symbol::Plus)) => {
+                    self.pos += 1;
+                    left += self.parse_multiplicative()?;
+                }
+                Some(Token::Symbol(Symbol::Minus)) => {
+                    self.pos += 1;
+                    left -= self.parse_multiplicative()?;
+                }
+                _ => break,
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> PreprocessResult<i64> {
+        let mut left = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol(Symbol::Asterisk)) => {
+                    self.pos += 1;
+                    left *= self.parse_unary()?;
+                }
+                Some(Token::Symbol(Symbol::Slash)) => {
+                    self.pos += 1;
+                    let right = self.parse_unary()?;
+                    if right == 0 {
+                        return Err(self.error("division by zero"));
+                    }
+                    left /= right;
+                }
+                Some(Token::Symbol(Symbol::Percent)) => {
+                    self.pos += 1;
+                    let right = self.parse_unary()?;
+                    if right == 0 {
+                        return Err(self.error("division by zero"));
+                    }
+                    left %= right;
+                }
+                _ => break,
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> PreprocessResult<i64> {
+        match self.peek() {
+            Some(Token::Symbol(Symbol::Bang)) => {
+                self.pos += 1;
+                let value = self.parse_unary()?;
+                Ok(i64::from(value == 0))
+            }
+            Some(Token::Symbol(Symbol::Minus)) => {
+                self.pos += 1;
+                Ok(-self.parse_unary()?)
+            }
+            Some(Token::Symbol(Symbol::Plus)) => {
+                self.pos += 1;
+                self.parse_unary()
+            }
+            Some(Token::Symbol(Symbol::Tilde)) => {
+                self.pos += 1;
+                Ok(!self.parse_unary()?)
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> PreprocessResult<i64> {
+        match self.peek() {
+            Some(Token::Literal(Literal::Integer(integer))) => {
+                let value = integer.value as i64;
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(Token::Literal(Literal::UnsignedInteger(integer))) => {
+                let value = integer.value as i64;
+                self.pos += 1;
+                Ok(value)
+            }
+            // An identifier left over after macro expansion is simply undefined,
+            // and the standard says undefined identifiers evaluate to zero.
+            Some(Token::Identifier(_)) => {
+                self.pos += 1;
+                Ok(0)
+            }
+            Some(Token::Symbol(Symbol::ParenOpen)) => {
+                self.pos += 1;
+                let value = self.parse_logical_or()?;
+                match self.peek() {
+                    Some(Token::Symbol(Symbol::ParenClose)) => {
+                        self.pos += 1;
+                        Ok(value)
+                    }
+                    _ => Err(self.error("expected ')'")),
+                }
+            }
+            _ => Err(self.error("expected an expression")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{IntegerBase, IntegerLiteral};
+
+    fn loc(line: u64) -> Loc {
+        Loc {
+            offset: 0,
+            line,
+            column: 1,
+        }
+    }
+
+    fn spanned(value: Token, line: u64) -> Spanned<Token> {
+        Spanned {
+            value,
+            start: loc(line),
+            end: loc(line),
+        }
+    }
+
+    fn int(value: u64) -> Token {
+        Token::Literal(Literal::Integer(IntegerLiteral {
+            value,
+            base: IntegerBase::Decimal,
+            suffix: None,
+        }))
+    }
+
+    fn ident(name: &str) -> Token {
+        Token::Identifier(name.to_string())
+    }
+
+    fn run(tokens: Vec<Spanned<Token>>) -> PreprocessResult<Vec<Token>> {
+        let mut preprocessor = Preprocessor {
+            macros: HashMap::new(),
+            pragma_once: HashSet::new(),
+            search_paths: Vec::new(),
+            lex_errors: Vec::new(),
+        };
+        let mut out = Vec::new();
+        preprocessor.process_tokens(Path::new("test.c"), &tokens, &mut out)?;
+        Ok(out.into_iter().map(|token| token.value).collect())
+    }
+
+    #[test]
+    fn object_like_macros_are_expanded_at_use_sites() {
+        let tokens = vec![
+            spanned(Token::Symbol(Symbol::Hash), 1),
+            spanned(ident("define"), 1),
+            spanned(ident("FOO"), 1),
+            spanned(int(42), 1),
+            spanned(ident("FOO"), 2),
+        ];
+        assert_eq!(run(tokens).unwrap(), vec![int(42)]);
+    }
+
+    #[test]
+    fn function_like_macros_substitute_arguments() {
+        // #define ADD(a, b) a + b
+        // ADD(1, 2)
+        let tokens = vec![
+            spanned(Token::Symbol(Symbol::Hash), 1),
+            spanned(ident("define"), 1),
+            spanned(ident("ADD"), 1),
+            spanned(Token::Symbol(Symbol::ParenOpen), 1),
+            spanned(ident("a"), 1),
+            spanned(Token::Symbol(Symbol::Comma), 1),
+            spanned(ident("b"), 1),
+            spanned(Token::Symbol(Symbol::ParenClose), 1),
+            spanned(ident("a"), 1),
+            spanned(Token::Symbol(Symbol::Plus), 1),
+            spanned(ident("b"), 1),
+            spanned(ident("ADD"), 2),
+            spanned(Token::Symbol(Symbol::ParenOpen), 2),
+            spanned(int(1), 2),
+            spanned(Token::Symbol(Symbol::Comma), 2),
+            spanned(int(2), 2),
+            spanned(Token::Symbol(Symbol::ParenClose), 2),
+        ];
+        assert_eq!(
+            run(tokens).unwrap(),
+            vec![int(1), Token::Symbol(Symbol::Plus), int(2)]
+        );
+    }
+
+    #[test]
+    fn a_macro_does_not_recursively_expand_itself() {
+        // #define FOO FOO
+        // FOO
+        let tokens = vec![
+            spanned(Token::Symbol(Symbol::Hash), 1),
+            spanned(ident("define"), 1),
+            spanned(ident("FOO"), 1),
+            spanned(ident("FOO"), 1),
+            spanned(ident("FOO"), 2),
+        ];
+        assert_eq!(run(tokens).unwrap(), vec![ident("FOO")]);
+    }
+
+    #[test]
+    fn ifdef_and_else_pick_the_active_branch() {
+        // #ifdef FOO
+        //     1
+        // #else
+        //     2
+        // #endif
+        let tokens = vec![
+            spanned(Token::Symbol(Symbol::Hash), 1),
+            spanned(ident("ifdef"), 1),
+            spanned(ident("FOO"), 1),
+            spanned(int(1), 2),
+            spanned(Token::Symbol(Symbol::Hash), 3),
+            spanned(Token::Keyword(Keyword::Else), 3),
+            spanned(int(2), 4),
+            spanned(Token::Symbol(Symbol::Hash), 5),
+            spanned(ident("endif"), 5),
+        ];
+        assert_eq!(run(tokens).unwrap(), vec![int(2)]);
+    }
+
+    #[test]
+    fn if_evaluates_a_constant_expression() {
+        // #if 1 + 1 == 2
+        //     42
+        // #endif
+        let tokens = vec![
+            spanned(Token::Symbol(Symbol::Hash), 1),
+            spanned(Token::Keyword(Keyword::If), 1),
+            spanned(int(1), 1),
+            spanned(Token::Symbol(Symbol::Plus), 1),
+            spanned(int(1), 1),
+            spanned(Token::Symbol(Symbol::EqualsEquals), 1),
+            spanned(int(2), 1),
+            spanned(int(42), 2),
+            spanned(Token::Symbol(Symbol::Hash), 3),
+            spanned(ident("endif"), 3),
+        ];
+        assert_eq!(run(tokens).unwrap(), vec![int(42)]);
+    }
+
+    #[test]
+    fn undef_removes_a_macro_so_it_no_longer_expands() {
+        // #define FOO 1
+        // #undef FOO
+        // FOO
+        let tokens = vec![
+            spanned(Token::Symbol(Symbol::Hash), 1),
+            spanned(ident("define"), 1),
+            spanned(ident("FOO"), 1),
+            spanned(int(1), 1),
+            spanned(Token::Symbol(Symbol::Hash), 2),
+            spanned(ident("undef"), 2),
+            spanned(ident("FOO"), 2),
+            spanned(ident("FOO"), 3),
+        ];
+        assert_eq!(run(tokens).unwrap(), vec![ident("FOO")]);
+    }
+
+    #[test]
+    fn an_unknown_directive_is_an_error() {
+        let tokens = vec![
+            spanned(Token::Symbol(Symbol::Hash), 1),
+            spanned(ident("nonsense"), 1),
+        ];
+        assert!(matches!(
+            run(tokens),
+            Err(PreprocessError::UnknownDirective { .. })
+        ));
+    }
+
+    #[test]
+    fn an_endif_with_no_matching_if_is_an_error() {
+        let tokens = vec![
+            spanned(Token::Symbol(Symbol::Hash), 1),
+            spanned(ident("endif"), 1),
+        ];
+        assert!(matches!(
+            run(tokens),
+            Err(PreprocessError::UnmatchedConditional { .. })
+        ));
+    }
+}